@@ -0,0 +1,33 @@
+package rs.android_api_util;
+
+/**
+ * A {@code Runnable} which calls a Rust closure, created by {@code android_api_util::runnable}.
+ *
+ * Apps using the closure based APIs compile this class in, keeping it from being
+ * renamed or removed by the shrinker:
+ * {@code -keep class rs.android_api_util.RustRunnable { *; }}
+ */
+public final class RustRunnable implements Runnable {
+    private long closure;
+
+    private RustRunnable(long closure) {
+        this.closure = closure;
+    }
+
+    @Override
+    public void run() {
+        long closure;
+
+        synchronized (this) {
+            closure = this.closure;
+            this.closure = 0;
+        }
+
+        // The closure is consumed by its first run
+        if (closure != 0) {
+            runClosure(closure);
+        }
+    }
+
+    private static native void runClosure(long closure);
+}
@@ -0,0 +1,44 @@
+use crate::intent::Intent;
+use crate::{CompatEnv, Error};
+use jni::objects::JObject;
+
+/// Supports API 1
+///
+/// API 31
+///
+/// Whether `AlarmManager.setExact` and friends may be used. Always `true` below
+/// API 31, where exact alarms need no permission.
+pub fn can_schedule_exact_alarms(env: CompatEnv<'_>) -> Result<bool, Error> {
+    // API 1: https://developer.android.com/reference/android/content/Context#ALARM_SERVICE
+    let alarms = env.system_service("ALARM_SERVICE")?;
+
+    // API 31: https://developer.android.com/reference/android/app/AlarmManager#canScheduleExactAlarms()
+    let allowed = env.try_call_method(alarms, "canScheduleExactAlarms", "()Z", &[])?;
+
+    Ok(allowed.map(|x| x.z()).transpose()?.unwrap_or(true))
+}
+
+/// API 31
+///
+/// An intent opening the settings screen where the user can allow this app to
+/// schedule exact alarms, for when `can_schedule_exact_alarms` returns `false`.
+pub fn open_exact_alarm_settings_intent(env: CompatEnv<'_>) -> Result<JObject<'_>, Error> {
+    // API 1: https://developer.android.com/reference/android/provider/Settings
+    let settings = env.find_class("android/provider/Settings")?;
+
+    // API 31: https://developer.android.com/reference/android/provider/Settings#ACTION_REQUEST_SCHEDULE_EXACT_ALARM
+    let action = match env.try_get_static_field(
+        settings,
+        "ACTION_REQUEST_SCHEDULE_EXACT_ALARM",
+        "Ljava/lang/String;",
+    )? {
+        Some(x) => env.char_sequence_to_string(x.l()?)?,
+        None => return Err(Error::Unsupported { api_required: 31 }),
+    };
+
+    let intent = Intent::new(env)?
+        .set_action(action)?
+        .set_data(&format!("package:{}", env.package_name()?))?;
+
+    Ok(intent.build())
+}
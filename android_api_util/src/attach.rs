@@ -0,0 +1,50 @@
+use crate::{CompatEnv, Error};
+use jni::objects::JObject;
+use jni::JavaVM;
+use ndk_glue::native_activity;
+use std::marker::PhantomData;
+
+/// Attaches the current thread to the VM for as long as this lives, detaching it
+/// again on drop even when a `?` returns early.
+///
+/// Threads which were already attached are left attached.
+pub struct AttachedThread {
+    vm: JavaVM,
+    was_attached: bool,
+    // Detaching only applies to the current thread, so this must stay on it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl AttachedThread {
+    pub fn new() -> Result<Self, Error> {
+        // Safety: the pointer comes from the `ANativeActivity` and lives as long as the process.
+        let vm = unsafe { JavaVM::from_raw(native_activity().vm()) }?;
+
+        let was_attached = vm.get_env().is_ok();
+        if !was_attached {
+            vm.attach_current_thread_permanently()?;
+        }
+
+        Ok(Self {
+            vm,
+            was_attached,
+            _not_send: PhantomData,
+        })
+    }
+
+    /// The env of this thread, with the activity as its context.
+    pub fn env(&self) -> Result<CompatEnv<'_>, Error> {
+        CompatEnv::from_env(
+            self.vm.get_env()?,
+            JObject::from(native_activity().activity()),
+        )
+    }
+}
+
+impl Drop for AttachedThread {
+    fn drop(&mut self) {
+        if !self.was_attached {
+            self.vm.detach_current_thread();
+        }
+    }
+}
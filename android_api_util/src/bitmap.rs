@@ -0,0 +1,48 @@
+use crate::resources::ResourceManager;
+use crate::{CompatEnv, Error};
+use jni::objects::{JObject, JValue};
+use jni::sys::jint;
+
+/// API 4
+///
+/// Downscales `bitmap` so neither side exceeds `max_dp`, keeping its aspect ratio,
+/// such as to stay within a notification's bitmap size limit. Bitmaps which
+/// already fit are returned as is.
+pub fn scale_bitmap<'a>(
+    env: CompatEnv<'a>,
+    bitmap: JObject<'a>,
+    max_dp: f32,
+) -> Result<JObject<'a>, Error> {
+    let metrics = ResourceManager::new(env, env.context)?.display_metrics()?;
+    let max_px = metrics.dp_to_px(max_dp);
+
+    // API 1: https://developer.android.com/reference/android/graphics/Bitmap#getWidth()
+    let width = env.call_method(bitmap, "getWidth", "()I", &[])?.i()?;
+    // API 1: https://developer.android.com/reference/android/graphics/Bitmap#getHeight()
+    let height = env.call_method(bitmap, "getHeight", "()I", &[])?.i()?;
+
+    let largest = width.max(height) as f32;
+    if largest <= max_px {
+        return Ok(bitmap);
+    }
+
+    let scale = max_px / largest;
+    let scaled = |x: jint| ((x as f32 * scale) as jint).max(1);
+
+    // API 1: https://developer.android.com/reference/android/graphics/Bitmap#createScaledBitmap(android.graphics.Bitmap,%20int,%20int,%20boolean)
+    let bitmap = env
+        .call_static_method(
+            "android/graphics/Bitmap",
+            "createScaledBitmap",
+            "(Landroid/graphics/Bitmap;IIZ)Landroid/graphics/Bitmap;",
+            &[
+                JValue::Object(bitmap),
+                JValue::Int(scaled(width)),
+                JValue::Int(scaled(height)),
+                JValue::Bool(true as u8),
+            ],
+        )?
+        .l()?;
+
+    Ok(bitmap)
+}
@@ -0,0 +1,111 @@
+use crate::{CompatEnv, Error};
+use jni::objects::{JObject, JValue};
+use jni::strings::JNIString;
+use jni::sys::{jint, jlong};
+
+#[derive(Copy, Clone)]
+pub struct Bundle<'a> {
+    internal: JObject<'a>,
+    env: CompatEnv<'a>,
+}
+
+impl<'a> Bundle<'a> {
+    /// API 1
+    pub fn new(env: CompatEnv<'a>) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/os/Bundle#Bundle()
+        let bundle = env.new_object("android/os/Bundle", "()V", &[])?;
+
+        Ok(Self {
+            internal: bundle,
+            env,
+        })
+    }
+
+    /// Wraps an existing `android.os.Bundle`.
+    pub fn from_object(env: CompatEnv<'a>, bundle: JObject<'a>) -> Self {
+        Self {
+            internal: bundle,
+            env,
+        }
+    }
+
+    /// API 1
+    pub fn put_string(
+        &self,
+        key: impl Into<JNIString>,
+        value: impl Into<JNIString>,
+    ) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/os/BaseBundle#putString(java.lang.String,%20java.lang.String)
+        self.env.call_method(
+            self.internal,
+            "putString",
+            "(Ljava/lang/String;Ljava/lang/String;)V",
+            &[
+                JValue::Object(*self.env.new_string(key)?),
+                JValue::Object(*self.env.new_string(value)?),
+            ],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    pub fn put_int(&self, key: impl Into<JNIString>, value: jint) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/os/BaseBundle#putInt(java.lang.String,%20int)
+        self.env.call_method(
+            self.internal,
+            "putInt",
+            "(Ljava/lang/String;I)V",
+            &[
+                JValue::Object(*self.env.new_string(key)?),
+                JValue::Int(value),
+            ],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    pub fn put_long(&self, key: impl Into<JNIString>, value: jlong) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/os/BaseBundle#putLong(java.lang.String,%20long)
+        self.env.call_method(
+            self.internal,
+            "putLong",
+            "(Ljava/lang/String;J)V",
+            &[
+                JValue::Object(*self.env.new_string(key)?),
+                JValue::Long(value),
+            ],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    pub fn put_boolean(&self, key: impl Into<JNIString>, value: bool) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/os/BaseBundle#putBoolean(java.lang.String,%20boolean)
+        self.env.call_method(
+            self.internal,
+            "putBoolean",
+            "(Ljava/lang/String;Z)V",
+            &[
+                JValue::Object(*self.env.new_string(key)?),
+                JValue::Bool(value as u8),
+            ],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    pub fn clear(&self) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/os/Bundle#clear()
+        self.env.call_method(self.internal, "clear", "()V", &[])?;
+
+        Ok(*self)
+    }
+
+    pub fn build(&self) -> JObject<'a> {
+        self.internal
+    }
+}
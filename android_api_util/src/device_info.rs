@@ -0,0 +1,49 @@
+use crate::{CompatEnv, Error};
+use jni::objects::{JClass, JString};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DeviceInfo {
+    /// API 4: https://developer.android.com/reference/android/os/Build#MANUFACTURER
+    pub manufacturer: String,
+    /// API 1: https://developer.android.com/reference/android/os/Build#MODEL
+    pub model: String,
+    /// API 1: https://developer.android.com/reference/android/os/Build#DEVICE
+    pub device: String,
+    /// API 1: https://developer.android.com/reference/android/os/Build#BRAND
+    pub brand: String,
+    /// API 1: https://developer.android.com/reference/android/os/Build.VERSION#RELEASE
+    pub release: String,
+}
+
+/// Supports API 1
+///
+/// API 4
+///
+/// Fields missing on older devices are left empty.
+pub fn device_info(env: CompatEnv<'_>) -> Result<DeviceInfo, Error> {
+    // API 1: https://developer.android.com/reference/android/os/Build
+    let build = env.find_class("android/os/Build")?;
+    // API 1: https://developer.android.com/reference/android/os/Build.VERSION
+    let version = env.find_class("android/os/Build$VERSION")?;
+
+    let load = |class: JClass<'_>, name: &str| -> Result<String, Error> {
+        let value = match env.try_get_static_field(class, name, "Ljava/lang/String;")? {
+            Some(x) => x.l()?,
+            None => return Ok(String::new()),
+        };
+
+        if value.is_null() {
+            return Ok(String::new());
+        }
+
+        Ok(env.get_string(JString::from(value))?.into())
+    };
+
+    Ok(DeviceInfo {
+        manufacturer: load(build, "MANUFACTURER")?,
+        model: load(build, "MODEL")?,
+        device: load(build, "DEVICE")?,
+        brand: load(build, "BRAND")?,
+        release: load(version, "RELEASE")?,
+    })
+}
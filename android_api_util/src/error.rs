@@ -0,0 +1,51 @@
+use jni::sys::jint;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A failure in the JNI layer, including a thrown Java exception.
+    Jni(jni::errors::Error),
+    /// The device's API level is below what the call requires.
+    Unsupported { api_required: jint },
+    /// A Java exception which was cleared, described by its `toString()`.
+    Exception(String),
+    /// The env's context is some other `Context`, such as a `Service`, where an
+    /// `Activity` is needed.
+    NotAnActivity,
+    /// No resource `name` of type `kind`, such as `drawable`, exists in the app's package.
+    MissingResource { name: String, kind: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Jni(e) => e.fmt(f),
+            Error::Unsupported { api_required } => {
+                write!(f, "Unsupported below API {}", api_required)
+            }
+            Error::Exception(description) => write!(f, "Java exception: {}", description),
+            Error::NotAnActivity => write!(f, "The context is not an Activity"),
+            Error::MissingResource { name, kind } => {
+                write!(f, "No {} resource named {}", kind, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Jni(e) => Some(e),
+            Error::Unsupported { .. }
+            | Error::Exception(_)
+            | Error::NotAnActivity
+            | Error::MissingResource { .. } => None,
+        }
+    }
+}
+
+impl From<jni::errors::Error> for Error {
+    fn from(e: jni::errors::Error) -> Self {
+        Error::Jni(e)
+    }
+}
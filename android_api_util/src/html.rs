@@ -0,0 +1,45 @@
+use crate::{CompatEnv, Error};
+use jni::objects::{JObject, JValue};
+
+/// Supports API 1
+///
+/// API 24
+///
+/// Parses `html` into a styled `CharSequence` through `Html.fromHtml`.
+pub fn html_to_charsequence<'a>(env: CompatEnv<'a>, html: &str) -> Result<JObject<'a>, Error> {
+    // API 1: https://developer.android.com/reference/android/text/Html
+    let class = env.find_class("android/text/Html")?;
+    let html = env.new_string(html)?;
+
+    if env.sdk_int()? >= 24 {
+        // API 24: https://developer.android.com/reference/android/text/Html#FROM_HTML_MODE_LEGACY
+        let mode = env
+            .get_static_field(class, "FROM_HTML_MODE_LEGACY", "I")?
+            .i()?;
+
+        // API 24: https://developer.android.com/reference/android/text/Html#fromHtml(java.lang.String,%20int)
+        let text = env
+            .call_static_method(
+                class,
+                "fromHtml",
+                "(Ljava/lang/String;I)Landroid/text/Spanned;",
+                &[JValue::Object(*html), JValue::Int(mode)],
+            )?
+            .l()?;
+
+        Ok(text)
+    } else {
+        // API 1: https://developer.android.com/reference/android/text/Html#fromHtml(java.lang.String)
+        // DEPRECATED in API 24
+        let text = env
+            .call_static_method(
+                class,
+                "fromHtml",
+                "(Ljava/lang/String;)Landroid/text/Spanned;",
+                &[JValue::Object(*html)],
+            )?
+            .l()?;
+
+        Ok(text)
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{CompatEnv, Error};
+use jni::objects::{JObject, JValue};
+
+/// Supports API 1
+///
+/// API 23
+///
+/// Wraps `bitmap`, such as a rendered avatar, in an `Icon`. `None` below API 23.
+pub fn icon_from_bitmap<'a>(
+    env: CompatEnv<'a>,
+    bitmap: JObject<'_>,
+) -> Result<Option<JObject<'a>>, Error> {
+    // API 23: https://developer.android.com/reference/android/graphics/drawable/Icon
+    let class = match env.try_find_class("android/graphics/drawable/Icon")? {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+
+    // API 23: https://developer.android.com/reference/android/graphics/drawable/Icon#createWithBitmap(android.graphics.Bitmap)
+    let icon = env
+        .call_static_method(
+            class,
+            "createWithBitmap",
+            "(Landroid/graphics/Bitmap;)Landroid/graphics/drawable/Icon;",
+            &[JValue::Object(bitmap)],
+        )?
+        .l()?;
+
+    Ok(Some(icon))
+}
@@ -0,0 +1,229 @@
+use crate::bundle::Bundle;
+use crate::{CompatEnv, Error};
+use jni::objects::{JObject, JString, JValue};
+use jni::strings::JNIString;
+use jni::sys::jint;
+
+#[derive(Copy, Clone)]
+pub struct Intent<'a> {
+    internal: JObject<'a>,
+    env: CompatEnv<'a>,
+}
+
+impl<'a> Intent<'a> {
+    /// API 1
+    pub fn new(env: CompatEnv<'a>) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Intent#Intent()
+        let intent = env.new_object("android/content/Intent", "()V", &[])?;
+
+        Ok(Self {
+            internal: intent,
+            env,
+        })
+    }
+
+    /// API 1
+    ///
+    /// An explicit intent for the class of `env.context`, usually the current activity.
+    pub fn for_context_class(env: CompatEnv<'a>) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Intent#Intent(android.content.Context,%20java.lang.Class%3C?%3E)
+        let intent = env.new_object(
+            "android/content/Intent",
+            "(Landroid/content/Context;Ljava/lang/Class;)V",
+            &[
+                JValue::Object(env.context),
+                JValue::Object(*env.get_object_class(env.context)?),
+            ],
+        )?;
+
+        Ok(Self {
+            internal: intent,
+            env,
+        })
+    }
+
+    /// Wraps an existing `android.content.Intent`.
+    pub fn from_object(env: CompatEnv<'a>, intent: JObject<'a>) -> Self {
+        Self {
+            internal: intent,
+            env,
+        }
+    }
+
+    /// API 1
+    pub fn set_flags(&self, flags: jint) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Intent#setFlags(int)
+        self.env.call_method(
+            self.internal,
+            "setFlags",
+            "(I)Landroid/content/Intent;",
+            &[JValue::Int(flags)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    pub fn add_flags(&self, flags: jint) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Intent#addFlags(int)
+        self.env.call_method(
+            self.internal,
+            "addFlags",
+            "(I)Landroid/content/Intent;",
+            &[JValue::Int(flags)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    pub fn set_action(&self, action: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Intent#setAction(java.lang.String)
+        self.env.call_method(
+            self.internal,
+            "setAction",
+            "(Ljava/lang/String;)Landroid/content/Intent;",
+            &[JValue::Object(*self.env.new_string(action)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 4
+    ///
+    /// Limits resolution to components of `package`, such as the app's own, so an
+    /// implicit intent can't reach other apps. Errors rather than leaving the intent
+    /// unrestricted below API 4.
+    pub fn set_package(&self, package: &str) -> Result<Self, Error> {
+        // API 4: https://developer.android.com/reference/android/content/Intent#setPackage(java.lang.String)
+        let set = self.env.try_call_method(
+            self.internal,
+            "setPackage",
+            "(Ljava/lang/String;)Landroid/content/Intent;",
+            &[JValue::Object(*self.env.new_string(package)?)],
+        )?;
+
+        match set {
+            Some(_) => Ok(*self),
+            None => Err(Error::Unsupported { api_required: 4 }),
+        }
+    }
+
+    /// API 4
+    ///
+    /// The package set through `set_package`, or `None` if resolution isn't limited.
+    pub fn package(&self) -> Result<Option<String>, Error> {
+        // API 4: https://developer.android.com/reference/android/content/Intent#getPackage()
+        let package =
+            self.env
+                .try_call_method(self.internal, "getPackage", "()Ljava/lang/String;", &[])?;
+
+        match package {
+            Some(package) => {
+                let package = package.l()?;
+
+                if package.is_null() {
+                    Ok(None)
+                } else {
+                    Ok(Some(self.env.get_string(JString::from(package))?.into()))
+                }
+            }
+            None => Err(Error::Unsupported { api_required: 4 }),
+        }
+    }
+
+    /// API 1
+    ///
+    /// Parses `uri` through `Uri.parse` and sets it as the intent's data.
+    pub fn set_data(&self, uri: &str) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/net/Uri#parse(java.lang.String)
+        let uri = self
+            .env
+            .call_static_method(
+                "android/net/Uri",
+                "parse",
+                "(Ljava/lang/String;)Landroid/net/Uri;",
+                &[JValue::Object(*self.env.new_string(uri)?)],
+            )?
+            .l()?;
+
+        // API 1: https://developer.android.com/reference/android/content/Intent#setData(android.net.Uri)
+        self.env.call_method(
+            self.internal,
+            "setData",
+            "(Landroid/net/Uri;)Landroid/content/Intent;",
+            &[JValue::Object(uri)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    pub fn put_extra_string(
+        &self,
+        key: impl Into<JNIString>,
+        value: impl Into<JNIString>,
+    ) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Intent#putExtra(java.lang.String,%20java.lang.String)
+        self.env.call_method(
+            self.internal,
+            "putExtra",
+            "(Ljava/lang/String;Ljava/lang/String;)Landroid/content/Intent;",
+            &[
+                JValue::Object(*self.env.new_string(key)?),
+                JValue::Object(*self.env.new_string(value)?),
+            ],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    pub fn put_extra_int(&self, key: impl Into<JNIString>, value: jint) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Intent#putExtra(java.lang.String,%20int)
+        self.env.call_method(
+            self.internal,
+            "putExtra",
+            "(Ljava/lang/String;I)Landroid/content/Intent;",
+            &[
+                JValue::Object(*self.env.new_string(key)?),
+                JValue::Int(value),
+            ],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    pub fn put_extra_bool(&self, key: impl Into<JNIString>, value: bool) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Intent#putExtra(java.lang.String,%20boolean)
+        self.env.call_method(
+            self.internal,
+            "putExtra",
+            "(Ljava/lang/String;Z)Landroid/content/Intent;",
+            &[
+                JValue::Object(*self.env.new_string(key)?),
+                JValue::Bool(value as u8),
+            ],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    pub fn put_extras(&self, extras: &Bundle<'_>) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Intent#putExtras(android.os.Bundle)
+        self.env.call_method(
+            self.internal,
+            "putExtras",
+            "(Landroid/os/Bundle;)Landroid/content/Intent;",
+            &[JValue::Object(extras.build())],
+        )?;
+
+        Ok(*self)
+    }
+
+    pub fn build(&self) -> JObject<'a> {
+        self.internal
+    }
+}
@@ -1,12 +1,41 @@
 use jni::descriptors::Desc;
-use jni::errors::Error;
-use jni::objects::{JClass, JObject, JValue};
+use jni::errors::Error as JniError;
+use jni::objects::{JClass, JObject, JString, JThrowable, JValue};
 use jni::strings::JNIString;
-use jni::{AttachGuard, JNIEnv};
+use jni::sys::{jchar, jint, jsize};
+use jni::{AttachGuard, JNIEnv, JavaVM};
 use ndk_glue::native_activity;
+use once_cell::sync::OnceCell;
 use std::ops::{Deref, DerefMut};
+use trace::Call;
 
+mod alarms;
+mod attach;
+mod bitmap;
+pub mod bundle;
+mod device_info;
+mod error;
+mod html;
+mod icon;
+pub mod intent;
+mod panic_bridge;
 pub mod resources;
+mod retry;
+mod runnable;
+mod strict;
+mod trace;
+
+pub use alarms::{can_schedule_exact_alarms, open_exact_alarm_settings_intent};
+pub use attach::AttachedThread;
+pub use bitmap::scale_bitmap;
+pub use device_info::{device_info, DeviceInfo};
+pub use error::Error;
+pub use html::html_to_charsequence;
+pub use icon::icon_from_bitmap;
+pub use panic_bridge::install_panic_bridge;
+pub use retry::RetryEnv;
+pub use runnable::runnable;
+pub use strict::StrictEnv;
 
 #[derive(Copy, Clone)]
 pub struct CompatEnv<'a> {
@@ -22,8 +51,11 @@ pub struct CompatEnv<'a> {
 
 impl<'a> CompatEnv<'a> {
     pub fn new(guard: &'a AttachGuard<'a>) -> Result<Self, Error> {
-        let env = **guard;
+        Self::from_env(**guard, JObject::from(native_activity().activity()))
+    }
 
+    /// For use where an env is already at hand, such as in `native` methods.
+    pub fn from_env(env: JNIEnv<'a>, context: JObject<'a>) -> Result<Self, Error> {
         // Should all be available.
         let class = env.find_class("java/lang/ClassNotFoundException")?;
         let field = env.find_class("java/lang/NoSuchFieldException")?;
@@ -34,7 +66,7 @@ impl<'a> CompatEnv<'a> {
 
         Ok(Self {
             env,
-            context: JObject::from(native_activity().activity()),
+            context,
             class_not_found_exception: class,
             no_such_field_exception: field,
             no_such_method_exception: method,
@@ -46,27 +78,36 @@ impl<'a> CompatEnv<'a> {
 
     fn try_do<T>(
         &self,
-        val: Result<T, Error>,
+        call: Call,
+        val: Result<T, JniError>,
         ignore: &'_ [JClass<'a>],
     ) -> Result<Option<T>, Error> {
         match val {
-            Ok(x) => Ok(Some(x)),
-            Err(Error::JavaException) => {
+            Ok(x) => {
+                call.log(self.env, "ok");
+                Ok(Some(x))
+            }
+            Err(JniError::JavaException) => {
                 let exception = self.env.exception_occurred()?;
 
                 self.env.exception_clear()?;
 
                 for i in ignore {
                     if self.env.is_instance_of(*exception, *i)? {
+                        call.log(self.env, "missing");
                         return Ok(None);
                     }
                 }
 
+                call.log(self.env, "threw");
                 self.env.throw(exception)?;
 
-                Err(Error::JavaException)
+                Err(JniError::JavaException.into())
+            }
+            Err(e) => {
+                call.log(self.env, &e.to_string());
+                Err(e.into())
             }
-            Err(e) => Err(e),
         }
     }
 
@@ -74,7 +115,11 @@ impl<'a> CompatEnv<'a> {
     where
         S: Into<JNIString>,
     {
+        let s: JNIString = s.into();
+        let call = Call::new("find_class", &s, "");
+
         self.try_do(
+            call,
             self.env.find_class(s),
             &[
                 self.class_not_found_exception,
@@ -94,7 +139,11 @@ impl<'a> CompatEnv<'a> {
         S: Into<JNIString>,
         T: Into<JNIString> + AsRef<str>,
     {
+        let name: JNIString = name.into();
+        let call = Call::new("get_field", &name, ty.as_ref());
+
         self.try_do(
+            call,
             self.env.get_field(obj, name, ty),
             &[self.no_such_field_exception, self.no_such_field_error],
         )
@@ -111,7 +160,11 @@ impl<'a> CompatEnv<'a> {
         U: Into<JNIString>,
         V: Into<JNIString> + AsRef<str>,
     {
+        let field: JNIString = field.into();
+        let call = Call::new("get_static_field", &field, sig.as_ref());
+
         self.try_do(
+            call,
             self.env.get_static_field(class, field, sig),
             &[self.no_such_field_exception, self.no_such_field_error],
         )
@@ -129,7 +182,11 @@ impl<'a> CompatEnv<'a> {
         S: Into<JNIString>,
         T: Into<JNIString> + AsRef<str>,
     {
+        let name: JNIString = name.into();
+        let call = Call::new("call_method", &name, sig.as_ref());
+
         self.try_do(
+            call,
             self.env.call_method(obj, name, sig, args),
             &[self.no_such_method_exception, self.no_such_method_error],
         )
@@ -147,7 +204,11 @@ impl<'a> CompatEnv<'a> {
         U: Into<JNIString>,
         V: Into<JNIString> + AsRef<str>,
     {
+        let name: JNIString = name.into();
+        let call = Call::new("call_static_method", &name, sig.as_ref());
+
         self.try_do(
+            call,
             self.env.call_static_method(class, name, sig, args),
             &[self.no_such_method_exception, self.no_such_method_error],
         )
@@ -163,11 +224,388 @@ impl<'a> CompatEnv<'a> {
         T: Desc<'a, JClass<'c>>,
         U: Into<JNIString> + AsRef<str>,
     {
+        let call = Call::new("new_object", &JNIString::from("<init>"), ctor_sig.as_ref());
+
         self.try_do(
+            call,
             self.env.new_object(class, ctor_sig, ctor_args),
             &[self.no_such_method_exception, self.no_such_method_error],
         )
     }
+
+    /// `JNIEnv::find_class`, traced by the `trace` feature like the calls below.
+    ///
+    /// These shadow `JNIEnv`'s methods of the same name, so calls made through the
+    /// `Deref` to `JNIEnv` are traced without changing their call sites.
+    pub fn find_class<S>(&self, name: S) -> Result<JClass<'a>, JniError>
+    where
+        S: Into<JNIString>,
+    {
+        let name: JNIString = name.into();
+        let call = Call::new("find_class", &name, "");
+
+        call.finish(self.env, self.env.find_class(name))
+    }
+
+    pub fn get_field<O, S, T>(&self, obj: O, name: S, ty: T) -> Result<JValue<'a>, JniError>
+    where
+        O: Into<JObject<'a>>,
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        let name: JNIString = name.into();
+        let call = Call::new("get_field", &name, ty.as_ref());
+
+        call.finish(self.env, self.env.get_field(obj, name, ty))
+    }
+
+    pub fn get_static_field<'c, T, U, V>(
+        &self,
+        class: T,
+        field: U,
+        sig: V,
+    ) -> Result<JValue<'a>, JniError>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Into<JNIString>,
+        V: Into<JNIString> + AsRef<str>,
+    {
+        let field: JNIString = field.into();
+        let call = Call::new("get_static_field", &field, sig.as_ref());
+
+        call.finish(self.env, self.env.get_static_field(class, field, sig))
+    }
+
+    pub fn call_method<O, S, T>(
+        &self,
+        obj: O,
+        name: S,
+        sig: T,
+        args: &[JValue],
+    ) -> Result<JValue<'a>, JniError>
+    where
+        O: Into<JObject<'a>>,
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        let name: JNIString = name.into();
+        let call = Call::new("call_method", &name, sig.as_ref());
+
+        call.finish(self.env, self.env.call_method(obj, name, sig, args))
+    }
+
+    pub fn call_static_method<'c, T, U, V>(
+        &self,
+        class: T,
+        name: U,
+        sig: V,
+        args: &[JValue],
+    ) -> Result<JValue<'a>, JniError>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Into<JNIString>,
+        V: Into<JNIString> + AsRef<str>,
+    {
+        let name: JNIString = name.into();
+        let call = Call::new("call_static_method", &name, sig.as_ref());
+
+        call.finish(
+            self.env,
+            self.env.call_static_method(class, name, sig, args),
+        )
+    }
+
+    pub fn new_object<'c, T, U>(
+        &self,
+        class: T,
+        ctor_sig: U,
+        ctor_args: &[JValue],
+    ) -> Result<JObject<'a>, JniError>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Into<JNIString> + AsRef<str>,
+    {
+        let call = Call::new("new_object", &JNIString::from("<init>"), ctor_sig.as_ref());
+
+        call.finish(self.env, self.env.new_object(class, ctor_sig, ctor_args))
+    }
+
+    /// Supports API 1
+    ///
+    /// API 4
+    pub fn sdk_int(&self) -> Result<jint, Error> {
+        static SDK_INT: OnceCell<jint> = OnceCell::new();
+
+        SDK_INT
+            .get_or_try_init(|| {
+                // API 1: https://developer.android.com/reference/android/os/Build.VERSION
+                let version = self.find_class("android/os/Build$VERSION")?;
+
+                // API 4: https://developer.android.com/reference/android/os/Build.VERSION#SDK_INT
+                if let Some(x) = self.try_get_static_field(version, "SDK_INT", "I")? {
+                    return Ok(x.i()?);
+                }
+
+                // API 1: https://developer.android.com/reference/android/os/Build.VERSION#SDK
+                let sdk = self
+                    .env
+                    .get_static_field(version, "SDK", "Ljava/lang/String;")?
+                    .l()?;
+                let sdk: String = self.env.get_string(JString::from(sdk))?.into();
+
+                Ok(sdk.parse().unwrap_or(1))
+            })
+            .copied()
+    }
+
+    /// Supports API 1
+    pub fn require_api(&self, level: jint) -> Result<(), Error> {
+        if self.sdk_int()? < level {
+            Err(Error::Unsupported {
+                api_required: level,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn call_method_or<O, S, T, F, E>(
+        &self,
+        obj: O,
+        name: S,
+        sig: T,
+        args: &[JValue],
+        fallback: F,
+    ) -> Result<JValue<'a>, Error>
+    where
+        O: Into<JObject<'a>>,
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+        F: FnOnce() -> Result<JValue<'a>, E>,
+        E: Into<Error>,
+    {
+        match self.try_call_method(obj, name, sig, args)? {
+            Some(x) => Ok(x),
+            None => fallback().map_err(Into::into),
+        }
+    }
+
+    pub fn new_object_or<'c, T, U, F, E>(
+        &self,
+        class: T,
+        ctor_sig: U,
+        ctor_args: &[JValue],
+        fallback: F,
+    ) -> Result<JObject<'a>, Error>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Into<JNIString> + AsRef<str>,
+        F: FnOnce() -> Result<JObject<'a>, E>,
+        E: Into<Error>,
+    {
+        match self.try_new_object(class, ctor_sig, ctor_args)? {
+            Some(x) => Ok(x),
+            None => fallback().map_err(Into::into),
+        }
+    }
+
+    /// API 1
+    ///
+    /// Builds the string from UTF-16 through `String(char[])` rather than
+    /// `NewStringUTF`, so strings containing characters outside the BMP (such
+    /// as emoji) never depend on how the device's JNI handles modified UTF-8.
+    pub fn new_string_checked(&self, s: &str) -> Result<JString<'a>, Error> {
+        let chars = s.encode_utf16().collect::<Vec<jchar>>();
+
+        let array = self.env.new_char_array(chars.len() as jsize)?;
+        self.env.set_char_array_region(array, 0, &chars)?;
+
+        // API 1: https://developer.android.com/reference/java/lang/String#String(char[])
+        let string = self.new_object(
+            "java/lang/String",
+            "([C)V",
+            &[JValue::Object(JObject::from(array))],
+        )?;
+
+        Ok(JString::from(string))
+    }
+
+    /// API 1
+    ///
+    /// Must not be called while an exception is pending.
+    fn describe(&self, exception: JThrowable<'_>) -> Result<String, Error> {
+        // API 1: https://developer.android.com/reference/java/lang/Throwable#toString()
+        let description = self
+            .env
+            .call_method(exception, "toString", "()Ljava/lang/String;", &[])?
+            .l()?;
+
+        Ok(self.env.get_string(JString::from(description))?.into())
+    }
+
+    /// API 1
+    ///
+    /// Converts any `CharSequence`, such as a `String` or a styled `Spanned`.
+    pub fn char_sequence_to_string(&self, seq: JObject<'_>) -> Result<String, Error> {
+        // API 1: https://developer.android.com/reference/java/lang/CharSequence#toString()
+        let string = self
+            .env
+            .call_method(seq, "toString", "()Ljava/lang/String;", &[])?
+            .l()?;
+
+        Ok(self.env.get_string(JString::from(string))?.into())
+    }
+
+    /// API 1
+    ///
+    /// The context, checked to be an `Activity` for calls which only exist there.
+    pub fn activity(&self) -> Result<JObject<'a>, Error> {
+        // API 1: https://developer.android.com/reference/android/app/Activity
+        if self
+            .env
+            .is_instance_of(self.context, "android/app/Activity")?
+        {
+            Ok(self.context)
+        } else {
+            Err(Error::NotAnActivity)
+        }
+    }
+
+    /// API 1
+    ///
+    /// Runs `f` on the activity's UI thread, later unless already on it. `f` receives
+    /// a `CompatEnv` for that thread, see `runnable` for what the app must include.
+    pub fn run_on_ui_thread(
+        &self,
+        f: impl FnOnce(&CompatEnv<'_>) + Send + 'static,
+    ) -> Result<(), Error> {
+        let activity = self.activity()?;
+        let runnable = runnable(*self, f)?;
+
+        // API 1: https://developer.android.com/reference/android/app/Activity#runOnUiThread(java.lang.Runnable)
+        self.call_method(
+            activity,
+            "runOnUiThread",
+            "(Ljava/lang/Runnable;)V",
+            &[JValue::Object(runnable)],
+        )?;
+
+        Ok(())
+    }
+
+    /// Errors with `NullPtr(what)` when `obj` is null, where `what` names the call
+    /// which returned it.
+    pub fn null_check<'b>(
+        &self,
+        obj: JObject<'b>,
+        what: &'static str,
+    ) -> Result<JObject<'b>, Error> {
+        if obj.is_null() {
+            Err(JniError::NullPtr(what).into())
+        } else {
+            Ok(obj)
+        }
+    }
+
+    /// `None` when `obj` is null.
+    pub fn into_option<'b>(&self, obj: JObject<'b>) -> Option<JObject<'b>> {
+        Some(obj).filter(|x| !x.is_null())
+    }
+
+    /// API 1
+    ///
+    /// The system service named by the `Context` constant `field`, such as
+    /// `"NOTIFICATION_SERVICE"`. Errors if the device has no such service.
+    pub fn system_service(&self, field: &str) -> Result<JObject<'a>, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Context
+        let name = self
+            .env
+            .get_static_field("android/content/Context", field, "Ljava/lang/String;")?
+            .l()?;
+
+        // API 1: https://developer.android.com/reference/android/content/Context#getSystemService(java.lang.String)
+        let service = self
+            .env
+            .call_method(
+                self.context,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[JValue::Object(name)],
+            )?
+            .l()?;
+
+        self.null_check(service, "getSystemService")
+    }
+
+    /// The VM, which unlike the env may be sent to other threads and attached there.
+    pub fn java_vm(&self) -> Result<JavaVM, Error> {
+        Ok(self.env.get_java_vm()?)
+    }
+
+    /// Wraps this env so every call clears the exceptions it throws, returning
+    /// them as `Error::Exception` instead of leaving them pending.
+    pub fn strict(&self) -> StrictEnv<'a> {
+        StrictEnv(*self)
+    }
+
+    /// Wraps this env so `find_class` and `new_object` are tried up to `attempts`
+    /// times, clearing any pending exception between attempts.
+    ///
+    /// A missing class is retried like any other failure, so don't use this for
+    /// `try_find_class` style probes. Zero attempts behaves like one.
+    pub fn with_retry(&self, attempts: u32) -> RetryEnv<'a> {
+        RetryEnv {
+            env: *self,
+            attempts,
+        }
+    }
+
+    /// API 1
+    pub fn log_error(&self, tag: &str, err: &Error) -> Result<(), Error> {
+        let mut message = err.to_string();
+
+        let exception = self.env.exception_occurred()?;
+        if !exception.is_null() {
+            // Calling into Java with a pending exception isn't allowed
+            self.env.exception_clear()?;
+
+            message = format!("{}: {}", message, self.describe(exception)?);
+        }
+
+        // API 1: https://developer.android.com/reference/android/util/Log#e(java.lang.String,%20java.lang.String)
+        self.env.call_static_method(
+            "android/util/Log",
+            "e",
+            "(Ljava/lang/String;Ljava/lang/String;)I",
+            &[
+                JValue::Object(*self.env.new_string(tag)?),
+                JValue::Object(*self.env.new_string(message)?),
+            ],
+        )?;
+
+        if !exception.is_null() {
+            self.env.throw(exception)?;
+        }
+
+        Ok(())
+    }
+
+    /// API 1
+    pub fn package_name(&self) -> Result<String, Error> {
+        static PACKAGE: OnceCell<String> = OnceCell::new();
+
+        PACKAGE
+            .get_or_try_init(|| {
+                // API 1: https://developer.android.com/reference/android/content/Context#getPackageName()
+                let package = self
+                    .env
+                    .call_method(self.context, "getPackageName", "()Ljava/lang/String;", &[])?
+                    .l()?;
+
+                Ok(self.env.get_string(JString::from(package))?.into())
+            })
+            .cloned()
+    }
 }
 
 impl<'a> Deref for CompatEnv<'a> {
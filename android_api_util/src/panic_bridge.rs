@@ -0,0 +1,37 @@
+use jni::JavaVM;
+use once_cell::sync::OnceCell;
+use std::panic;
+
+static VM: OnceCell<JavaVM> = OnceCell::new();
+
+/// Installs a panic hook which throws a `java.lang.RuntimeException` carrying
+/// the panic message on the panicking thread, if it is attached to `vm`, such
+/// as one from `CompatEnv::java_vm`. Calls after the first do nothing.
+///
+/// The hook only makes the exception pending, so the panic must still be
+/// caught with `std::panic::catch_unwind` before returning to Java, at which
+/// point the exception is raised on the Java side instead of the process
+/// aborting. Code which catches a panic and carries on must clear the
+/// exception itself, or the next JNI call sees it. An exception which is
+/// already pending is left as is. The previously installed hook keeps
+/// running afterwards.
+pub fn install_panic_bridge(vm: JavaVM) {
+    if VM.set(vm).is_err() {
+        return;
+    }
+
+    let previous = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        // Fails when this thread isn't attached, in which case there's nobody to throw to.
+        if let Some(env) = VM.get().and_then(|vm| vm.get_env().ok()) {
+            // A pending exception may well be what caused the panic
+            if let Ok(false) = env.exception_check() {
+                // API 1: https://developer.android.com/reference/java/lang/RuntimeException
+                let _ = env.throw_new("java/lang/RuntimeException", info.to_string());
+            }
+        }
+
+        previous(info);
+    }));
+}
@@ -1,19 +1,79 @@
-use crate::CompatEnv;
-use jni::errors::Error;
-use jni::objects::{JObject, JValue};
+use crate::{CompatEnv, Error};
+use jni::objects::{JObject, JString, JValue};
 use jni::strings::JNIString;
-use jni::sys::jint;
+use jni::sys::{jint, jsize};
 use std::collections::HashMap;
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DisplayMetrics {
+    /// API 1: https://developer.android.com/reference/android/util/DisplayMetrics#density
+    pub density: f32,
+    /// API 1: https://developer.android.com/reference/android/util/DisplayMetrics#widthPixels
+    pub width_px: i32,
+    /// API 1: https://developer.android.com/reference/android/util/DisplayMetrics#heightPixels
+    pub height_px: i32,
+    /// API 4: https://developer.android.com/reference/android/util/DisplayMetrics#densityDpi
+    pub density_dpi: i32,
+}
+
+impl DisplayMetrics {
+    pub fn dp_to_px(&self, dp: f32) -> f32 {
+        dp * self.density
+    }
+}
+
+/// API 1
+///
+/// A one-off `getIdentifier` against `env.context`, for when caching through a
+/// `ResourceManager` isn't worth it.
+pub fn lookup_id(
+    env: CompatEnv<'_>,
+    name: impl Into<JNIString>,
+    kind: impl Into<JNIString>,
+) -> Result<Option<jint>, Error> {
+    // API 1: https://developer.android.com/reference/android/content/Context#getResources()
+    let resources = env
+        .call_method(
+            env.context,
+            "getResources",
+            "()Landroid/content/res/Resources;",
+            &[],
+        )?
+        .l()?;
+
+    // API 1: https://developer.android.com/reference/android/content/res/Resources#getIdentifier(java.lang.String,%20java.lang.String,%20java.lang.String)
+    let id = env
+        .call_method(
+            resources,
+            "getIdentifier",
+            "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;)I",
+            &[
+                JValue::Object(*env.new_string(name)?),
+                JValue::Object(*env.new_string(kind)?),
+                JValue::Object(*env.new_string(env.package_name()?)?),
+            ],
+        )?
+        .i()?;
+
+    Ok(Some(id).filter(|x| *x != 0))
+}
+
 pub struct ResourceManager<'a> {
     env: CompatEnv<'a>,
+    context: JObject<'a>,
     resources: JObject<'a>,
     package: JObject<'a>,
-    previous_resources: HashMap<String, jint>,
+    /// `None` caches a resource confirmed missing. Failed lookups are never cached.
+    previous_resources: HashMap<(String, String), Option<jint>>,
 }
 
 impl<'a> ResourceManager<'a> {
     pub const DRAWABLE: &'static str = "drawable";
+    pub const STRING: &'static str = "string";
+    pub const ARRAY: &'static str = "array";
+    pub const BOOL: &'static str = "bool";
+    pub const INTEGER: &'static str = "integer";
+    pub const ATTR: &'static str = "attr";
 
     /// API 1
     pub fn new(env: CompatEnv<'a>, context: JObject<'a>) -> Result<Self, Error> {
@@ -34,6 +94,7 @@ impl<'a> ResourceManager<'a> {
 
         let x = Self {
             env,
+            context,
             resources,
             package,
             previous_resources: HashMap::new(),
@@ -43,16 +104,18 @@ impl<'a> ResourceManager<'a> {
     }
 
     /// API 1
+    ///
+    /// Returns `0` for a missing resource, as `getIdentifier` does.
     pub fn get(
         &mut self,
         name: impl AsRef<str> + Into<JNIString>,
-        kind: impl Into<JNIString>,
+        kind: impl AsRef<str> + Into<JNIString>,
     ) -> Result<jint, Error> {
-        match self.previous_resources.get(name.as_ref()) {
-            Some(x) => Ok(*x),
-            None => {
-                let owned = name.as_ref().to_owned();
+        let key = (name.as_ref().to_owned(), kind.as_ref().to_owned());
 
+        match self.previous_resources.get(&key) {
+            Some(x) => Ok(x.unwrap_or(0)),
+            None => {
                 // API 1: https://developer.android.com/reference/android/content/res/Resources#getIdentifier(java.lang.String,%20java.lang.String,%20java.lang.String)
                 let val = self
                     .env
@@ -70,12 +133,279 @@ impl<'a> ResourceManager<'a> {
 
                 match val {
                     Ok(x) => {
-                        self.previous_resources.insert(owned, x);
+                        self.previous_resources
+                            .insert(key, Some(x).filter(|x| *x != 0));
                         Ok(x)
                     }
-                    x => x,
+                    Err(e) => Err(e.into()),
                 }
             }
         }
     }
+
+    /// API 1
+    ///
+    /// Resolves every `(name, kind)` pair up front, so later `get`s are cache hits.
+    /// Missing resources are cached too, and `get_existing` reports them without
+    /// querying again.
+    pub fn prefetch(&mut self, names: &[(&str, &str)]) -> Result<(), Error> {
+        // Each lookup creates two strings, released together when the frame is popped
+        self.env.push_local_frame(2 * names.len() as jint)?;
+
+        let result = names
+            .iter()
+            .try_for_each(|(name, kind)| self.get(*name, *kind).map(|_| ()));
+
+        self.env.pop_local_frame(JObject::null())?;
+
+        result
+    }
+
+    /// Supports API 1
+    ///
+    /// API 23
+    pub fn get_icon(
+        &mut self,
+        name: impl AsRef<str> + Into<JNIString>,
+    ) -> Result<Option<JObject<'a>>, Error> {
+        // API 23: https://developer.android.com/reference/android/graphics/drawable/Icon
+        let class = match self.env.try_find_class("android/graphics/drawable/Icon")? {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+
+        let id = self.get_existing(name, Self::DRAWABLE)?;
+
+        // API 23: https://developer.android.com/reference/android/graphics/drawable/Icon#createWithResource(android.content.Context,%20int)
+        let icon = self
+            .env
+            .call_static_method(
+                class,
+                "createWithResource",
+                "(Landroid/content/Context;I)Landroid/graphics/drawable/Icon;",
+                &[JValue::Object(self.context), JValue::Int(id)],
+            )?
+            .l()?;
+
+        Ok(Some(icon))
+    }
+
+    /// API 1
+    ///
+    /// Decodes the drawable `name` into a `Bitmap`. Errors if there's no such drawable,
+    /// or it isn't a bitmap, such as a vector drawable.
+    pub fn decode_drawable_bitmap(
+        &mut self,
+        name: impl AsRef<str> + Into<JNIString>,
+    ) -> Result<JObject<'a>, Error> {
+        let id = self.get_existing(name, Self::DRAWABLE)?;
+
+        // API 1: https://developer.android.com/reference/android/graphics/BitmapFactory#decodeResource(android.content.res.Resources,%20int)
+        let bitmap = self
+            .env
+            .call_static_method(
+                "android/graphics/BitmapFactory",
+                "decodeResource",
+                "(Landroid/content/res/Resources;I)Landroid/graphics/Bitmap;",
+                &[JValue::Object(self.resources), JValue::Int(id)],
+            )?
+            .l()?;
+
+        self.env.null_check(bitmap, "BitmapFactory.decodeResource")
+    }
+
+    /// API 1
+    pub fn get_string_formatted(
+        &mut self,
+        name: impl AsRef<str> + Into<JNIString>,
+        args: &[&str],
+    ) -> Result<String, Error> {
+        let id = self.get(name, Self::STRING)?;
+
+        let array =
+            self.env
+                .new_object_array(args.len() as jsize, "java/lang/Object", JObject::null())?;
+
+        for (i, arg) in args.iter().enumerate() {
+            self.env
+                .set_object_array_element(array, i as jsize, *self.env.new_string(*arg)?)?;
+        }
+
+        // API 1: https://developer.android.com/reference/android/content/res/Resources#getString(int,%20java.lang.Object...)
+        let value = self
+            .env
+            .call_method(
+                self.resources,
+                "getString",
+                "(I[Ljava/lang/Object;)Ljava/lang/String;",
+                &[JValue::Int(id), JValue::Object(JObject::from(array))],
+            )?
+            .l()?;
+
+        Ok(self.env.get_string(JString::from(value))?.into())
+    }
+
+    /// API 1
+    pub fn get_string_array(
+        &mut self,
+        name: impl AsRef<str> + Into<JNIString>,
+    ) -> Result<Vec<String>, Error> {
+        let id = self.get_existing(name, Self::ARRAY)?;
+
+        // API 1: https://developer.android.com/reference/android/content/res/Resources#getStringArray(int)
+        let array = self
+            .env
+            .call_method(
+                self.resources,
+                "getStringArray",
+                "(I)[Ljava/lang/String;",
+                &[JValue::Int(id)],
+            )?
+            .l()?
+            .into_inner();
+
+        let len = self.env.get_array_length(array)?;
+
+        (0..len)
+            .map(|i| {
+                let value = self.env.get_object_array_element(array, i)?;
+                Ok(self.env.get_string(JString::from(value))?.into())
+            })
+            .collect()
+    }
+
+    /// API 1
+    pub fn get_int_array(
+        &mut self,
+        name: impl AsRef<str> + Into<JNIString>,
+    ) -> Result<Vec<jint>, Error> {
+        let id = self.get_existing(name, Self::ARRAY)?;
+
+        // API 1: https://developer.android.com/reference/android/content/res/Resources#getIntArray(int)
+        let array = self
+            .env
+            .call_method(self.resources, "getIntArray", "(I)[I", &[JValue::Int(id)])?
+            .l()?
+            .into_inner();
+
+        let mut values = vec![0; self.env.get_array_length(array)? as usize];
+        self.env.get_int_array_region(array, 0, &mut values)?;
+
+        Ok(values)
+    }
+
+    /// API 1
+    ///
+    /// Like `get`, but errors with `MissingResource` where `get` would return `0`.
+    pub fn get_existing(
+        &mut self,
+        name: impl AsRef<str> + Into<JNIString>,
+        kind: &str,
+    ) -> Result<jint, Error> {
+        let owned = name.as_ref().to_owned();
+
+        match self.get(name, kind)? {
+            // `getIdentifier` returns 0 for a missing resource
+            0 => Err(Error::MissingResource {
+                name: owned,
+                kind: kind.into(),
+            }),
+            x => Ok(x),
+        }
+    }
+
+    /// API 1
+    pub fn get_bool(&mut self, name: impl AsRef<str> + Into<JNIString>) -> Result<bool, Error> {
+        let id = self.get_existing(name, Self::BOOL)?;
+
+        // API 1: https://developer.android.com/reference/android/content/res/Resources#getBoolean(int)
+        let value = self
+            .env
+            .call_method(self.resources, "getBoolean", "(I)Z", &[JValue::Int(id)])?
+            .z()?;
+
+        Ok(value)
+    }
+
+    /// API 1
+    pub fn get_integer(&mut self, name: impl AsRef<str> + Into<JNIString>) -> Result<jint, Error> {
+        let id = self.get_existing(name, Self::INTEGER)?;
+
+        // API 1: https://developer.android.com/reference/android/content/res/Resources#getInteger(int)
+        let value = self
+            .env
+            .call_method(self.resources, "getInteger", "(I)I", &[JValue::Int(id)])?
+            .i()?;
+
+        Ok(value)
+    }
+
+    /// API 4
+    pub fn display_metrics(&self) -> Result<DisplayMetrics, Error> {
+        // API 1: https://developer.android.com/reference/android/content/res/Resources#getDisplayMetrics()
+        let metrics = self
+            .env
+            .call_method(
+                self.resources,
+                "getDisplayMetrics",
+                "()Landroid/util/DisplayMetrics;",
+                &[],
+            )?
+            .l()?;
+
+        Ok(DisplayMetrics {
+            density: self.env.get_field(metrics, "density", "F")?.f()?,
+            width_px: self.env.get_field(metrics, "widthPixels", "I")?.i()?,
+            height_px: self.env.get_field(metrics, "heightPixels", "I")?.i()?,
+            density_dpi: self.env.get_field(metrics, "densityDpi", "I")?.i()?,
+        })
+    }
+
+    /// API 1
+    ///
+    /// Returns the `data` of the attribute as resolved against the context's theme.
+    pub fn resolve_attribute(&mut self, attr_name: &str) -> Result<jint, Error> {
+        let attr = self.get_existing(attr_name, Self::ATTR)?;
+
+        // API 1: https://developer.android.com/reference/android/content/Context#getTheme()
+        let theme = self
+            .env
+            .call_method(
+                self.context,
+                "getTheme",
+                "()Landroid/content/res/Resources$Theme;",
+                &[],
+            )?
+            .l()?;
+
+        // API 1: https://developer.android.com/reference/android/util/TypedValue#TypedValue()
+        let value = self.env.new_object("android/util/TypedValue", "()V", &[])?;
+
+        // API 1: https://developer.android.com/reference/android/content/res/Resources.Theme#resolveAttribute(int,%20android.util.TypedValue,%20boolean)
+        let found = self
+            .env
+            .call_method(
+                theme,
+                "resolveAttribute",
+                "(ILandroid/util/TypedValue;Z)Z",
+                &[
+                    JValue::Int(attr),
+                    JValue::Object(value),
+                    JValue::Bool(true as u8),
+                ],
+            )?
+            .z()?;
+
+        if !found {
+            return Err(Error::MissingResource {
+                name: attr_name.into(),
+                kind: Self::ATTR.into(),
+            });
+        }
+
+        // API 1: https://developer.android.com/reference/android/util/TypedValue#data
+        let data = self.env.get_field(value, "data", "I")?.i()?;
+
+        Ok(data)
+    }
 }
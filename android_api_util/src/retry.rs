@@ -0,0 +1,139 @@
+use crate::{CompatEnv, Error};
+use jni::descriptors::Desc;
+use jni::errors::Error as JniError;
+use jni::objects::{JClass, JObject, JValue};
+
+/// A `CompatEnv` which retries class lookups and object creation, for devices
+/// where they transiently fail under memory pressure.
+///
+/// Created through `CompatEnv::with_retry`.
+///
+/// Every failure is retried, including a genuine `ClassNotFoundException`, so
+/// probing for classes which may not exist is better left to `try_find_class`.
+#[derive(Copy, Clone)]
+pub struct RetryEnv<'a> {
+    pub(crate) env: CompatEnv<'a>,
+    pub(crate) attempts: u32,
+}
+
+impl<'a> RetryEnv<'a> {
+    fn retry<T>(&self, call: impl FnMut() -> Result<T, JniError>) -> Result<T, Error> {
+        Ok(retry(self.attempts, call, || self.env.exception_clear())?)
+    }
+
+    pub fn find_class<S>(&self, name: S) -> Result<JClass<'a>, Error>
+    where
+        S: AsRef<str>,
+    {
+        self.retry(|| self.env.find_class(name.as_ref()))
+    }
+
+    pub fn new_object<'c, T, U>(
+        &self,
+        class: T,
+        ctor_sig: U,
+        ctor_args: &[JValue],
+    ) -> Result<JObject<'a>, Error>
+    where
+        T: Desc<'a, JClass<'c>> + Copy,
+        U: AsRef<str>,
+    {
+        self.retry(|| self.env.new_object(class, ctor_sig.as_ref(), ctor_args))
+    }
+}
+
+/// Runs `call` until it succeeds or has been tried `attempts` times, at least once,
+/// calling `clear` after each failed attempt which threw. The last failure is
+/// returned as is.
+fn retry<T>(
+    attempts: u32,
+    mut call: impl FnMut() -> Result<T, JniError>,
+    mut clear: impl FnMut() -> Result<(), JniError>,
+) -> Result<T, JniError> {
+    for _ in 1..attempts {
+        match call() {
+            Ok(x) => return Ok(x),
+            Err(JniError::JavaException) => clear()?,
+            Err(_) => {}
+        }
+    }
+
+    call()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Fails with `fail` for the first `failures` calls, counting calls and clears.
+    struct Flaky {
+        failures: u32,
+        fail: fn() -> JniError,
+        calls: Cell<u32>,
+        clears: Cell<u32>,
+    }
+
+    impl Flaky {
+        fn new(failures: u32, fail: fn() -> JniError) -> Self {
+            Self {
+                failures,
+                fail,
+                calls: Cell::new(0),
+                clears: Cell::new(0),
+            }
+        }
+
+        fn call(&self) -> Result<u32, JniError> {
+            self.calls.set(self.calls.get() + 1);
+
+            if self.calls.get() <= self.failures {
+                Err((self.fail)())
+            } else {
+                Ok(self.calls.get())
+            }
+        }
+
+        fn clear(&self) -> Result<(), JniError> {
+            self.clears.set(self.clears.get() + 1);
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn retry_stops_at_first_success() {
+        let flaky = Flaky::new(1, || JniError::JavaException);
+
+        assert_eq!(retry(3, || flaky.call(), || flaky.clear()).unwrap(), 2);
+        assert_eq!(flaky.calls.get(), 2);
+        assert_eq!(flaky.clears.get(), 1);
+    }
+
+    #[test]
+    fn retry_returns_the_last_failure() {
+        let flaky = Flaky::new(u32::MAX, || JniError::NullPtr("last"));
+
+        assert!(matches!(
+            retry(3, || flaky.call(), || flaky.clear()),
+            Err(JniError::NullPtr("last"))
+        ));
+        assert_eq!(flaky.calls.get(), 3);
+        // Only exceptions need clearing
+        assert_eq!(flaky.clears.get(), 0);
+    }
+
+    #[test]
+    fn retry_tries_once_with_zero_attempts() {
+        for attempts in 0..=1 {
+            let flaky = Flaky::new(1, || JniError::JavaException);
+
+            assert!(matches!(
+                retry(attempts, || flaky.call(), || flaky.clear()),
+                Err(JniError::JavaException)
+            ));
+            assert_eq!(flaky.calls.get(), 1);
+            assert_eq!(flaky.clears.get(), 0);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{CompatEnv, Error};
+use jni::objects::{GlobalRef, JClass, JObject, JValue};
+use jni::sys::jlong;
+use jni::{JNIEnv, NativeMethod};
+use once_cell::sync::OnceCell;
+use std::ffi::c_void;
+use std::panic::{self, AssertUnwindSafe};
+
+/// A closure along with the context its `CompatEnv` is created for.
+struct Callback {
+    context: GlobalRef,
+    closure: Box<dyn FnOnce(&CompatEnv<'_>) + Send>,
+}
+
+/// API 1
+///
+/// Wraps `f` as a `java.lang.Runnable`, which calls it with a `CompatEnv` for the
+/// same context on whichever thread runs it, such as through `Handler.post`.
+///
+/// The `Runnable` is a `rs.android_api_util.RustRunnable`, which the app must
+/// compile in from this crate's `java` directory. `f` runs at most once, and is
+/// leaked if the `Runnable` never runs.
+pub fn runnable<'a>(
+    env: CompatEnv<'a>,
+    f: impl FnOnce(&CompatEnv<'_>) + Send + 'static,
+) -> Result<JObject<'a>, Error> {
+    let class = runnable_class(env)?;
+
+    let callback = Box::new(Callback {
+        context: env.new_global_ref(env.context)?,
+        closure: Box::new(f),
+    });
+    let callback = Box::into_raw(callback) as jlong;
+
+    match env.new_object(
+        JClass::from(class.as_obj()),
+        "(J)V",
+        &[JValue::Long(callback)],
+    ) {
+        Ok(runnable) => Ok(runnable),
+        Err(e) => {
+            // Safety: the pointer was never handed to Java, so nothing else frees it
+            drop(unsafe { Box::from_raw(callback as *mut Callback) });
+
+            Err(e.into())
+        }
+    }
+}
+
+/// Loads `RustRunnable` through the app's class loader, which unlike `FindClass`
+/// also works from natively attached threads, and registers its native method.
+fn runnable_class(env: CompatEnv<'_>) -> Result<&'static GlobalRef, Error> {
+    static CLASS: OnceCell<GlobalRef> = OnceCell::new();
+
+    CLASS.get_or_try_init(|| {
+        let strict = env.strict();
+
+        // API 1: https://developer.android.com/reference/android/content/Context#getClassLoader()
+        let loader = strict
+            .call_method(
+                env.context,
+                "getClassLoader",
+                "()Ljava/lang/ClassLoader;",
+                &[],
+            )?
+            .l()?;
+
+        // API 1: https://developer.android.com/reference/java/lang/ClassLoader#loadClass(java.lang.String)
+        let class = strict
+            .call_method(
+                loader,
+                "loadClass",
+                "(Ljava/lang/String;)Ljava/lang/Class;",
+                &[JValue::Object(
+                    *env.new_string("rs.android_api_util.RustRunnable")?,
+                )],
+            )?
+            .l()?;
+
+        env.register_native_methods(
+            JClass::from(class),
+            &[NativeMethod {
+                name: "runClosure".into(),
+                sig: "(J)V".into(),
+                fn_ptr: run_closure as *mut c_void,
+            }],
+        )?;
+
+        Ok(env.new_global_ref(class)?)
+    })
+}
+
+extern "system" fn run_closure(env: JNIEnv<'_>, _class: JClass<'_>, callback: jlong) {
+    // Safety: the pointer comes from `Box::into_raw` in `runnable`, and `RustRunnable`
+    // passes it here only once
+    let Callback { context, closure } = *unsafe { Box::from_raw(callback as *mut Callback) };
+
+    let ran = panic::catch_unwind(AssertUnwindSafe(|| {
+        // Fails only with an exception pending, which then propagates to Java
+        if let Ok(env) = CompatEnv::from_env(env, context.as_obj()) {
+            closure(&env);
+        }
+    }));
+
+    // Unwinding into Java would abort, so the panic surfaces as an exception instead,
+    // unless `install_panic_bridge` already threw one.
+    if ran.is_err() && !env.exception_check().unwrap_or(true) {
+        let _ = env.throw_new("java/lang/RuntimeException", "A Rust closure panicked");
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{CompatEnv, Error};
+use jni::descriptors::Desc;
+use jni::errors::Error as JniError;
+use jni::objects::{JClass, JObject, JValue};
+use jni::strings::JNIString;
+
+/// A `CompatEnv` which never leaves an exception pending after a call.
+///
+/// Created through `CompatEnv::strict`.
+#[derive(Copy, Clone)]
+pub struct StrictEnv<'a>(pub(crate) CompatEnv<'a>);
+
+impl<'a> StrictEnv<'a> {
+    fn check<T>(&self, val: Result<T, JniError>) -> Result<T, Error> {
+        let env = self.0;
+
+        // Also catches exceptions left over by an earlier call which succeeded
+        let exception = env.exception_occurred()?;
+        if exception.is_null() {
+            return Ok(val?);
+        }
+
+        env.exception_clear()?;
+
+        Err(Error::Exception(env.describe(exception)?))
+    }
+
+    pub fn find_class<S>(&self, name: S) -> Result<JClass<'a>, Error>
+    where
+        S: Into<JNIString>,
+    {
+        self.check(self.0.find_class(name))
+    }
+
+    pub fn get_field<O, S, T>(&self, obj: O, name: S, ty: T) -> Result<JValue<'a>, Error>
+    where
+        O: Into<JObject<'a>>,
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        self.check(self.0.get_field(obj, name, ty))
+    }
+
+    pub fn get_static_field<'c, T, U, V>(
+        &self,
+        class: T,
+        field: U,
+        sig: V,
+    ) -> Result<JValue<'a>, Error>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Into<JNIString>,
+        V: Into<JNIString> + AsRef<str>,
+    {
+        self.check(self.0.get_static_field(class, field, sig))
+    }
+
+    pub fn call_method<O, S, T>(
+        &self,
+        obj: O,
+        name: S,
+        sig: T,
+        args: &[JValue],
+    ) -> Result<JValue<'a>, Error>
+    where
+        O: Into<JObject<'a>>,
+        S: Into<JNIString>,
+        T: Into<JNIString> + AsRef<str>,
+    {
+        self.check(self.0.call_method(obj, name, sig, args))
+    }
+
+    pub fn call_static_method<'c, T, U, V>(
+        &self,
+        class: T,
+        name: U,
+        sig: V,
+        args: &[JValue],
+    ) -> Result<JValue<'a>, Error>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Into<JNIString>,
+        V: Into<JNIString> + AsRef<str>,
+    {
+        self.check(self.0.call_static_method(class, name, sig, args))
+    }
+
+    pub fn new_object<'c, T, U>(
+        &self,
+        class: T,
+        ctor_sig: U,
+        ctor_args: &[JValue],
+    ) -> Result<JObject<'a>, Error>
+    where
+        T: Desc<'a, JClass<'c>>,
+        U: Into<JNIString> + AsRef<str>,
+    {
+        self.check(self.0.new_object(class, ctor_sig, ctor_args))
+    }
+}
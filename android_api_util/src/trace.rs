@@ -0,0 +1,103 @@
+use jni::errors::Result;
+use jni::strings::JNIStr;
+use jni::JNIEnv;
+
+/// A JNI call made through one of `CompatEnv`'s `try_*` or raw call helpers,
+/// logged to logcat once its outcome is known when the `trace` feature is enabled.
+///
+/// Without the feature this is zero sized and logging does nothing.
+pub(crate) struct Call {
+    #[cfg(feature = "trace")]
+    description: String,
+}
+
+impl Call {
+    #[cfg(feature = "trace")]
+    pub(crate) fn new(kind: &str, name: &JNIStr, sig: &str) -> Self {
+        Self {
+            description: format!("{} {}{}", kind, std::borrow::Cow::from(name), sig),
+        }
+    }
+
+    #[cfg(not(feature = "trace"))]
+    pub(crate) fn new(_kind: &str, _name: &JNIStr, _sig: &str) -> Self {
+        Self {}
+    }
+
+    #[cfg(feature = "trace")]
+    fn message(&self, outcome: &str) -> String {
+        format!("{} -> {}", self.description, outcome)
+    }
+
+    /// Must not be called while an exception is pending.
+    #[cfg(feature = "trace")]
+    pub(crate) fn log(&self, env: JNIEnv<'_>, outcome: &str) {
+        let message = self.message(outcome);
+
+        let logged = (|| -> jni::errors::Result<()> {
+            let tag = env.new_string("android_api_util")?;
+            let message = env.new_string(message)?;
+
+            // API 1: https://developer.android.com/reference/android/util/Log#d(java.lang.String,%20java.lang.String)
+            env.call_static_method(
+                "android/util/Log",
+                "d",
+                "(Ljava/lang/String;Ljava/lang/String;)I",
+                &[tag.into(), message.into()],
+            )?;
+
+            env.delete_local_ref(*tag)?;
+            env.delete_local_ref(*message)
+        })();
+
+        // Tracing is best effort and must never leave an exception behind.
+        if logged.is_err() {
+            let _ = env.exception_clear();
+        }
+    }
+
+    #[cfg(not(feature = "trace"))]
+    pub(crate) fn log(&self, _env: JNIEnv<'_>, _outcome: &str) {}
+
+    /// Logs the outcome of a call which, unlike those through `try_do`, leaves
+    /// any exception it threw pending for the caller.
+    #[cfg(feature = "trace")]
+    pub(crate) fn finish<T>(&self, env: JNIEnv<'_>, val: Result<T>) -> Result<T> {
+        match &val {
+            Ok(_) => self.log(env, "ok"),
+            Err(jni::errors::Error::JavaException) => {
+                if let Ok(exception) = env.exception_occurred() {
+                    if !exception.is_null() && env.exception_clear().is_ok() {
+                        self.log(env, "threw");
+                        let _ = env.throw(exception);
+                    }
+                }
+            }
+            Err(e) => self.log(env, &e.to_string()),
+        }
+
+        val
+    }
+
+    #[cfg(not(feature = "trace"))]
+    pub(crate) fn finish<T>(&self, _env: JNIEnv<'_>, val: Result<T>) -> Result<T> {
+        val
+    }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use super::*;
+    use jni::strings::JNIString;
+
+    #[test]
+    fn message_names_the_call_and_outcome() {
+        let name = JNIString::from("getPackage");
+        let call = Call::new("call_method", &name, "()Ljava/lang/String;");
+
+        assert_eq!(
+            call.message("threw"),
+            "call_method getPackage()Ljava/lang/String; -> threw"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use crate::notification::NotificationBuilder;
+use android_api_util::{CompatEnv, Error};
+use jni::objects::{JObject, JValue};
+use jni::sys::{jint, jlong, jsize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How one kind of alert behaves on a notification.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Alert<T> {
+    /// The user's default, through `setDefaults`.
+    Default,
+    /// An explicit value, through the alert's own setter.
+    Custom(T),
+    /// Neither, so the alert is left off.
+    #[default]
+    Off,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Lights {
+    pub argb: jint,
+    pub on_ms: jint,
+    pub off_ms: jint,
+}
+
+/// The sound, vibration and lights of a notification, for devices below API 26
+/// where they aren't decided by the channel.
+///
+/// A `DEFAULT_*` flag overrides the matching explicit setter, so mixing the two
+/// by hand silently drops the explicit value. `apply` only sets the defaults
+/// left as `Alert::Default`, and the explicit values for the rest.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LegacyAlerting {
+    /// A sound's `Uri` as a string, such as `content://settings/system/notification_sound`.
+    pub sound: Alert<String>,
+    /// Alternating off and on durations in milliseconds, starting with off.
+    pub vibration: Alert<Vec<jlong>>,
+    pub lights: Alert<Lights>,
+}
+
+impl LegacyAlerting {
+    /// API 11
+    ///
+    /// DEPRECATED in API 26
+    pub fn apply(
+        &self,
+        builder: &NotificationBuilder<'_>,
+        env: CompatEnv<'_>,
+    ) -> Result<(), Error> {
+        // API 1: https://developer.android.com/reference/android/app/Notification
+        let class = env.find_class("android/app/Notification")?;
+        let flag = |name: &str| -> Result<jint, Error> {
+            Ok(env.get_static_field(class, name, "I")?.i()?)
+        };
+
+        let mut defaults = 0;
+        if self.sound == Alert::Default {
+            // API 1: https://developer.android.com/reference/android/app/Notification#DEFAULT_SOUND
+            defaults |= flag("DEFAULT_SOUND")?;
+        }
+        if self.vibration == Alert::Default {
+            // API 1: https://developer.android.com/reference/android/app/Notification#DEFAULT_VIBRATE
+            defaults |= flag("DEFAULT_VIBRATE")?;
+        }
+        if self.lights == Alert::Default {
+            // API 1: https://developer.android.com/reference/android/app/Notification#DEFAULT_LIGHTS
+            defaults |= flag("DEFAULT_LIGHTS")?;
+        }
+
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setDefaults(int)
+        env.call_method(
+            builder.internal,
+            "setDefaults",
+            "(I)Landroid/app/Notification$Builder;",
+            &[JValue::Int(defaults)],
+        )?;
+
+        if let Alert::Custom(sound) = &self.sound {
+            // API 1: https://developer.android.com/reference/android/net/Uri#parse(java.lang.String)
+            let uri = env
+                .call_static_method(
+                    "android/net/Uri",
+                    "parse",
+                    "(Ljava/lang/String;)Landroid/net/Uri;",
+                    &[JValue::Object(*env.new_string(sound)?)],
+                )?
+                .l()?;
+
+            // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setSound(android.net.Uri)
+            env.call_method(
+                builder.internal,
+                "setSound",
+                "(Landroid/net/Uri;)Landroid/app/Notification$Builder;",
+                &[JValue::Object(uri)],
+            )?;
+        }
+
+        if let Alert::Custom(pattern) = &self.vibration {
+            let array = env.new_long_array(pattern.len() as jsize)?;
+            env.set_long_array_region(array, 0, pattern)?;
+
+            // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setVibrate(long[])
+            env.call_method(
+                builder.internal,
+                "setVibrate",
+                "([J)Landroid/app/Notification$Builder;",
+                &[JValue::Object(JObject::from(array))],
+            )?;
+        }
+
+        if let Alert::Custom(lights) = &self.lights {
+            // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setLights(int,%20int,%20int)
+            env.call_method(
+                builder.internal,
+                "setLights",
+                "(III)Landroid/app/Notification$Builder;",
+                &[
+                    JValue::Int(lights.argb),
+                    JValue::Int(lights.on_ms),
+                    JValue::Int(lights.off_ms),
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alerting_round_trips_through_json() {
+        let alerting = LegacyAlerting {
+            sound: Alert::Default,
+            vibration: Alert::Custom(vec![0, 250, 100, 250]),
+            lights: Alert::Custom(Lights {
+                argb: 0xff00_ff00_u32 as jint,
+                on_ms: 500,
+                off_ms: 2000,
+            }),
+        };
+
+        let json = serde_json::to_string(&alerting).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<LegacyAlerting>(&json).unwrap(),
+            alerting
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use android_api_util::{CompatEnv, Error};
+use jni::sys::jint;
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum AudioUsage {
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_ALARM
+    Alarm,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_ASSISTANCE_ACCESSIBILITY
+    AssistanceAccessibility,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_ASSISTANCE_NAVIGATION_GUIDANCE
+    AssistanceNavigationGuidance,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_ASSISTANCE_SONIFICATION
+    AssistanceSonification,
+    /// API 26: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_ASSISTANT
+    Assistant,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_GAME
+    Game,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_MEDIA
+    Media,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_NOTIFICATION
+    Notification,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_NOTIFICATION_COMMUNICATION_DELAYED
+    /// DEPRECATED in API 33
+    NotificationCommunicationDelayed,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_NOTIFICATION_COMMUNICATION_INSTANT
+    /// DEPRECATED in API 33
+    NotificationCommunicationInstant,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_NOTIFICATION_COMMUNICATION_REQUEST
+    /// DEPRECATED in API 33
+    NotificationCommunicationRequest,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_NOTIFICATION_EVENT
+    NotificationEvent,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_NOTIFICATION_RINGTONE
+    NotificationRingtone,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_UNKNOWN
+    Unknown,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_VOICE_COMMUNICATION
+    VoiceCommunication,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#USAGE_VOICE_COMMUNICATION_SIGNALLING
+    VoiceCommunicationSignalling,
+}
+
+impl AudioUsage {
+    fn internal_name(&self) -> &'static str {
+        match self {
+            AudioUsage::Alarm => "USAGE_ALARM",
+            AudioUsage::AssistanceAccessibility => "USAGE_ASSISTANCE_ACCESSIBILITY",
+            AudioUsage::AssistanceNavigationGuidance => "USAGE_ASSISTANCE_NAVIGATION_GUIDANCE",
+            AudioUsage::AssistanceSonification => "USAGE_ASSISTANCE_SONIFICATION",
+            AudioUsage::Assistant => "USAGE_ASSISTANT",
+            AudioUsage::Game => "USAGE_GAME",
+            AudioUsage::Media => "USAGE_MEDIA",
+            AudioUsage::Notification => "USAGE_NOTIFICATION",
+            AudioUsage::NotificationCommunicationDelayed => {
+                "USAGE_NOTIFICATION_COMMUNICATION_DELAYED"
+            }
+            AudioUsage::NotificationCommunicationInstant => {
+                "USAGE_NOTIFICATION_COMMUNICATION_INSTANT"
+            }
+            AudioUsage::NotificationCommunicationRequest => {
+                "USAGE_NOTIFICATION_COMMUNICATION_REQUEST"
+            }
+            AudioUsage::NotificationEvent => "USAGE_NOTIFICATION_EVENT",
+            AudioUsage::NotificationRingtone => "USAGE_NOTIFICATION_RINGTONE",
+            AudioUsage::Unknown => "USAGE_UNKNOWN",
+            AudioUsage::VoiceCommunication => "USAGE_VOICE_COMMUNICATION",
+            AudioUsage::VoiceCommunicationSignalling => "USAGE_VOICE_COMMUNICATION_SIGNALLING",
+        }
+    }
+
+    /// API 21
+    ///
+    /// API 26 for `Assistant`
+    pub fn internal_value(&self, env: CompatEnv<'_>) -> Result<jint, Error> {
+        match self {
+            AudioUsage::Assistant => env.require_api(26)?,
+            _ => env.require_api(21)?,
+        }
+
+        // API 21: https://developer.android.com/reference/android/media/AudioAttributes
+        let class = env.find_class("android/media/AudioAttributes")?;
+        // API 21 as per most possible fields for Self
+        let value = env.get_static_field(class, self.internal_name(), "I")?;
+        Ok(value.i()?)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum AudioContentType {
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#CONTENT_TYPE_MOVIE
+    Movie,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#CONTENT_TYPE_MUSIC
+    Music,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#CONTENT_TYPE_SONIFICATION
+    Sonification,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#CONTENT_TYPE_SPEECH
+    Speech,
+    /// API 21: https://developer.android.com/reference/android/media/AudioAttributes#CONTENT_TYPE_UNKNOWN
+    Unknown,
+}
+
+impl AudioContentType {
+    fn internal_name(&self) -> &'static str {
+        match self {
+            AudioContentType::Movie => "CONTENT_TYPE_MOVIE",
+            AudioContentType::Music => "CONTENT_TYPE_MUSIC",
+            AudioContentType::Sonification => "CONTENT_TYPE_SONIFICATION",
+            AudioContentType::Speech => "CONTENT_TYPE_SPEECH",
+            AudioContentType::Unknown => "CONTENT_TYPE_UNKNOWN",
+        }
+    }
+
+    /// API 21
+    pub fn internal_value(&self, env: CompatEnv<'_>) -> Result<jint, Error> {
+        env.require_api(21)?;
+
+        // API 21: https://developer.android.com/reference/android/media/AudioAttributes
+        let class = env.find_class("android/media/AudioAttributes")?;
+        // API 21 as per all possible fields for Self
+        let value = env.get_static_field(class, self.internal_name(), "I")?;
+        Ok(value.i()?)
+    }
+}
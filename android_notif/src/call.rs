@@ -0,0 +1,83 @@
+use crate::channel::NotificationChannelID;
+use crate::notification::{NotificationBuilder, Priority};
+use crate::style::CallStyle;
+use android_api_util::{CompatEnv, Error};
+use jni::objects::{JObject, JValue};
+
+/// The parts of an incoming call notification. The intents are `PendingIntent`s.
+#[derive(Clone)]
+pub struct CallNotification<'a> {
+    pub caller_name: String,
+    /// Shows the app's call screen, which also presents the answer and decline
+    /// buttons below API 31.
+    pub full_screen_intent: JObject<'a>,
+    pub answer_intent: JObject<'a>,
+    pub decline_intent: JObject<'a>,
+}
+
+impl<'a> CallNotification<'a> {
+    /// Supports API 11
+    ///
+    /// API 31
+    ///
+    /// A builder with the incoming call recipe applied: the call category, the full
+    /// screen intent, ongoing and high priority. From API 31 it also uses
+    /// `Notification.CallStyle`, which provides the answer and decline buttons.
+    ///
+    /// The channel should have `Importance::High`, and a small icon must still be set.
+    pub fn builder(
+        &self,
+        env: CompatEnv<'a>,
+        channel_id: NotificationChannelID<'_>,
+    ) -> Result<NotificationBuilder<'a>, Error> {
+        let builder = NotificationBuilder::new(env, channel_id)?
+            .set_title(&*self.caller_name)?
+            .set_full_screen_intent(self.full_screen_intent, true)?
+            .set_ongoing(true)?
+            .set_priority(Priority::High)?;
+
+        // API 1: https://developer.android.com/reference/android/app/Notification
+        let class = env.find_class("android/app/Notification")?;
+        // API 21: https://developer.android.com/reference/android/app/Notification#CATEGORY_CALL
+        if let Some(category) =
+            env.try_get_static_field(class, "CATEGORY_CALL", "Ljava/lang/String;")?
+        {
+            let category = env.char_sequence_to_string(category.l()?)?;
+            builder.set_category(&category)?;
+        }
+
+        if env.sdk_int()? >= 31 {
+            let style = CallStyle::for_incoming_call(
+                env,
+                self.person(env)?,
+                self.decline_intent,
+                self.answer_intent,
+            )?;
+
+            builder.set_style(&style)?;
+        }
+
+        Ok(builder)
+    }
+
+    /// API 28
+    fn person(&self, env: CompatEnv<'a>) -> Result<JObject<'a>, Error> {
+        // API 28: https://developer.android.com/reference/android/app/Person.Builder#Builder()
+        let person = env.new_object("android/app/Person$Builder", "()V", &[])?;
+
+        // API 28: https://developer.android.com/reference/android/app/Person.Builder#setName(java.lang.CharSequence)
+        env.call_method(
+            person,
+            "setName",
+            "(Ljava/lang/CharSequence;)Landroid/app/Person$Builder;",
+            &[JValue::Object(*env.new_string(&self.caller_name)?)],
+        )?;
+
+        // API 28: https://developer.android.com/reference/android/app/Person.Builder#build()
+        let person = env
+            .call_method(person, "build", "()Landroid/app/Person;", &[])?
+            .l()?;
+
+        Ok(person)
+    }
+}
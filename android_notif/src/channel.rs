@@ -1,9 +1,14 @@
-use android_api_util::CompatEnv;
-use jni::errors::Error;
-use jni::objects::JValue;
+use crate::notification::NotificationManager;
+use android_api_util::{CompatEnv, Error};
+use jni::objects::{JObject, JValue};
 use jni::sys::jint;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize};
+#[cfg(feature = "serde")]
+use std::fmt;
 
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Importance {
     /// API 24: https://developer.android.com/reference/android/app/NotificationManager#IMPORTANCE_DEFAULT
     Default,
@@ -34,29 +39,156 @@ impl Importance {
         }
     }
 
+    fn legacy_priority_name(&self) -> &'static str {
+        match self {
+            Importance::Default | Importance::Unspecified => "PRIORITY_DEFAULT",
+            Importance::High => "PRIORITY_HIGH",
+            Importance::Low => "PRIORITY_LOW",
+            Importance::Max => "PRIORITY_MAX",
+            Importance::Min | Importance::None => "PRIORITY_MIN",
+        }
+    }
+
+    /// Supports API 1
+    ///
     /// API 24
+    ///
+    /// Below API 24 this is the closest `Notification.PRIORITY_*` instead,
+    /// or `0` (`PRIORITY_DEFAULT`) below API 16.
     pub fn internal_value(&self, env: CompatEnv<'_>) -> Result<jint, Error> {
         // API 1: https://developer.android.com/reference/android/app/NotificationManager
         let class = env.find_class("android/app/NotificationManager")?;
         // API 24 as per all possible fields for Self
-        let value = env.get_static_field(class, self.internal_name(), "I")?;
-        Ok(value.i()?)
+        if let Some(value) = env.try_get_static_field(class, self.internal_name(), "I")? {
+            return Ok(value.i()?);
+        }
+
+        // API 1: https://developer.android.com/reference/android/app/Notification
+        let class = env.find_class("android/app/Notification")?;
+        // API 16 as per all possible priorities: https://developer.android.com/reference/android/app/Notification#PRIORITY_DEFAULT
+        let value = env.try_get_static_field(class, self.legacy_priority_name(), "I")?;
+
+        Ok(value.map(|x| x.i()).transpose()?.unwrap_or(0))
+    }
+
+    /// Supports API 1
+    ///
+    /// API 24
+    ///
+    /// Values unknown to this crate map to `Unspecified`. Below API 24 the legacy
+    /// priorities are ambiguous, so the first importance sharing one is returned.
+    pub fn from_internal_value(value: jint, env: CompatEnv<'_>) -> Result<Self, Error> {
+        const ALL: [Importance; 7] = [
+            Importance::Default,
+            Importance::High,
+            Importance::Low,
+            Importance::Max,
+            Importance::Min,
+            Importance::None,
+            Importance::Unspecified,
+        ];
+
+        for importance in ALL.iter() {
+            if importance.internal_value(env)? == value {
+                return Ok(*importance);
+            }
+        }
+
+        Ok(Importance::Unspecified)
     }
 }
 
 pub type NotificationChannelID<'a> = &'a str;
 
+/// Only `Serialize` with the `serde` feature, as a borrowed id can't hold every
+/// string, such as JSON with escapes. Deserialize an `OwnedNotificationChannel` instead.
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct NotificationChannel<'a> {
     pub id: NotificationChannelID<'a>,
     pub name: String,
     pub desc: Option<String>,
     pub importance: Importance,
+    /// API 30: `(parent_channel_id, conversation_id)`, see https://developer.android.com/reference/android/app/NotificationChannel#setConversationId(java.lang.String,%20java.lang.String)
+    pub conversation: Option<(String, String)>,
+    /// API 29: https://developer.android.com/reference/android/app/NotificationChannel#setAllowBubbles(boolean)
+    pub allow_bubbles: Option<bool>,
+}
+
+/// A `NotificationChannel` which owns its id, such as one read from a config file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OwnedNotificationChannel {
+    pub id: String,
+    pub name: String,
+    pub desc: Option<String>,
+    pub importance: Importance,
+    /// API 30: `(parent_channel_id, conversation_id)`, see https://developer.android.com/reference/android/app/NotificationChannel#setConversationId(java.lang.String,%20java.lang.String)
+    pub conversation: Option<(String, String)>,
+    /// API 29: https://developer.android.com/reference/android/app/NotificationChannel#setAllowBubbles(boolean)
+    pub allow_bubbles: Option<bool>,
+}
+
+impl OwnedNotificationChannel {
+    pub fn as_channel(&self) -> NotificationChannel<'_> {
+        NotificationChannel {
+            id: &self.id,
+            name: self.name.clone(),
+            desc: self.desc.clone(),
+            importance: self.importance,
+            conversation: self.conversation.clone(),
+            allow_bubbles: self.allow_bubbles,
+        }
+    }
+}
+
+/// The live state of a channel as the user may have changed it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelInfo {
+    pub id: String,
+    pub name: String,
+    pub importance: Importance,
+    pub group: Option<String>,
+    /// Whether the user turned the channel off, leaving its importance at `None`.
+    pub blocked: bool,
+}
+
+impl ChannelInfo {
+    /// API 26
+    pub(crate) fn from_object(channel: JObject<'_>, env: CompatEnv<'_>) -> Result<Self, Error> {
+        // API 26: https://developer.android.com/reference/android/app/NotificationChannel#getId()
+        let id = env
+            .call_method(channel, "getId", "()Ljava/lang/String;", &[])?
+            .l()?;
+        // API 26: https://developer.android.com/reference/android/app/NotificationChannel#getName()
+        let name = env
+            .call_method(channel, "getName", "()Ljava/lang/CharSequence;", &[])?
+            .l()?;
+        // API 26: https://developer.android.com/reference/android/app/NotificationChannel#getImportance()
+        let importance = env.call_method(channel, "getImportance", "()I", &[])?.i()?;
+        // API 26: https://developer.android.com/reference/android/app/NotificationChannel#getGroup()
+        let group = env
+            .call_method(channel, "getGroup", "()Ljava/lang/String;", &[])?
+            .l()?;
+
+        let importance = Importance::from_internal_value(importance, env)?;
+
+        Ok(Self {
+            id: env.char_sequence_to_string(id)?,
+            name: env.char_sequence_to_string(name)?,
+            importance,
+            blocked: importance == Importance::None,
+            group: env
+                .into_option(group)
+                .map(|x| env.char_sequence_to_string(x))
+                .transpose()?,
+        })
+    }
 }
 
 /// Supports API 1
 ///
 /// API 4
-fn notification_channel_available(env: CompatEnv<'_>) -> Result<bool, Error> {
+pub(crate) fn notification_channel_available(env: CompatEnv<'_>) -> Result<bool, Error> {
     // API 1: https://developer.android.com/reference/android/os/Build.VERSION
     let version_class = env.find_class("android/os/Build$VERSION")?;
     // API 4: https://developer.android.com/reference/android/os/Build.VERSION_CODES
@@ -86,17 +218,11 @@ fn notification_channel_available(env: CompatEnv<'_>) -> Result<bool, Error> {
         .unwrap_or(false))
 }
 
-/// Supports API 1
-///
 /// API 26
-pub fn create_notification_channel(
-    channel_cfg: NotificationChannel<'_>,
-    env: CompatEnv<'_>,
-) -> Result<(), Error> {
-    if !notification_channel_available(env)? {
-        return Ok(());
-    }
-
+pub(crate) fn channel_object<'a>(
+    channel_cfg: &NotificationChannel<'_>,
+    env: CompatEnv<'a>,
+) -> Result<JObject<'a>, Error> {
     let name = env.new_string(&channel_cfg.name)?;
     let desc = channel_cfg
         .desc
@@ -127,32 +253,192 @@ pub fn create_notification_channel(
         )?;
     }
 
-    // API 1: https://developer.android.com/reference/android/content/Context#NOTIFICATION_SERVICE
-    let notif_manager = env
-        .get_static_field(
-            "android/content/Context",
-            "NOTIFICATION_SERVICE",
-            "Ljava/lang/String;",
-        )?
-        .l()?;
-
-    // API 1: https://developer.android.com/reference/android/content/Context#getSystemService(java.lang.String)
-    let manager = env
-        .call_method(
-            env.context,
-            "getSystemService",
-            "(Ljava/lang/String;)Ljava/lang/Object;",
-            &[JValue::Object(notif_manager)],
-        )?
-        .l()?;
-
-    // API 26: https://developer.android.com/reference/android/app/NotificationManager#createNotificationChannel(android.app.NotificationChannel)
+    if let Some((parent_id, conversation_id)) = &channel_cfg.conversation {
+        if env.sdk_int()? >= 30 {
+            // API 30: https://developer.android.com/reference/android/app/NotificationChannel#setConversationId(java.lang.String,%20java.lang.String)
+            env.call_method(
+                channel,
+                "setConversationId",
+                "(Ljava/lang/String;Ljava/lang/String;)V",
+                &[
+                    JValue::Object(*env.new_string(parent_id)?),
+                    JValue::Object(*env.new_string(conversation_id)?),
+                ],
+            )?;
+        }
+    }
+
+    if let Some(allow_bubbles) = channel_cfg.allow_bubbles {
+        if env.sdk_int()? >= 29 {
+            // API 29: https://developer.android.com/reference/android/app/NotificationChannel#setAllowBubbles(boolean)
+            env.call_method(
+                channel,
+                "setAllowBubbles",
+                "(Z)V",
+                &[JValue::Bool(allow_bubbles as u8)],
+            )?;
+        }
+    }
+
+    Ok(channel)
+}
+
+/// Supports API 1
+///
+/// API 26
+pub fn create_notification_channel(
+    channel_cfg: NotificationChannel<'_>,
+    env: CompatEnv<'_>,
+) -> Result<(), Error> {
+    NotificationManager::new(env)?.ensure_channel(&channel_cfg)?;
+
+    Ok(())
+}
+
+/// Supports API 1
+///
+/// API 26
+pub fn create_notification_channels(
+    channels: &[NotificationChannel<'_>],
+    env: CompatEnv<'_>,
+) -> Result<usize, Error> {
+    if !notification_channel_available(env)? {
+        return Ok(0);
+    }
+
+    // API 1: https://developer.android.com/reference/java/util/ArrayList#ArrayList(int)
+    let list = env.new_object(
+        "java/util/ArrayList",
+        "(I)V",
+        &[JValue::Int(channels.len() as jint)],
+    )?;
+    let list = env.get_list(list)?;
+
+    for channel_cfg in channels {
+        list.add(channel_object(channel_cfg, env)?)?;
+    }
+
+    let manager = NotificationManager::new(env)?;
+
+    // API 26: https://developer.android.com/reference/android/app/NotificationManager#createNotificationChannels(java.util.List%3Candroid.app.NotificationChannel%3E)
     env.call_method(
-        manager,
-        "createNotificationChannel",
-        "(Landroid/app/NotificationChannel;)V",
-        &[JValue::Object(channel)],
+        manager.internal,
+        "createNotificationChannels",
+        "(Ljava/util/List;)V",
+        &[JValue::Object(*list)],
     )?;
 
-    Ok(())
+    Ok(channels.len())
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum ChannelConfigError<E> {
+    Parse(E),
+    Android(Error),
+}
+
+#[cfg(feature = "serde")]
+impl<E: fmt::Display> fmt::Display for ChannelConfigError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelConfigError::Parse(e) => write!(f, "Invalid channel config: {}", e),
+            ChannelConfigError::Android(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<E: std::error::Error + 'static> std::error::Error for ChannelConfigError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChannelConfigError::Parse(e) => Some(e),
+            ChannelConfigError::Android(e) => Some(e),
+        }
+    }
+}
+
+/// Supports API 1
+///
+/// API 26
+///
+/// Creates every channel in `json`, a list of `OwnedNotificationChannel`s.
+#[cfg(feature = "serde_json")]
+pub fn apply_channel_config(
+    json: &str,
+    env: CompatEnv<'_>,
+) -> Result<usize, ChannelConfigError<serde_json::Error>> {
+    let channels: Vec<OwnedNotificationChannel> =
+        serde_json::from_str(json).map_err(ChannelConfigError::Parse)?;
+
+    create_owned_channels(&channels, env).map_err(ChannelConfigError::Android)
+}
+
+/// Supports API 1
+///
+/// API 26
+///
+/// Creates every channel in `config`, a list of `OwnedNotificationChannel`s in any
+/// serde format. `apply_channel_config` takes JSON directly.
+#[cfg(feature = "serde")]
+pub fn apply_channel_config_from<'de, D: Deserializer<'de>>(
+    config: D,
+    env: CompatEnv<'_>,
+) -> Result<usize, ChannelConfigError<D::Error>> {
+    let channels =
+        Vec::<OwnedNotificationChannel>::deserialize(config).map_err(ChannelConfigError::Parse)?;
+
+    create_owned_channels(&channels, env).map_err(ChannelConfigError::Android)
+}
+
+#[cfg(feature = "serde")]
+fn create_owned_channels(
+    channels: &[OwnedNotificationChannel],
+    env: CompatEnv<'_>,
+) -> Result<usize, Error> {
+    let channels = channels
+        .iter()
+        .map(OwnedNotificationChannel::as_channel)
+        .collect::<Vec<_>>();
+
+    create_notification_channels(&channels, env)
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owned_channel_round_trips_through_json() {
+        let channel = OwnedNotificationChannel {
+            id: "chat \"general\"".to_owned(),
+            name: "General".to_owned(),
+            desc: Some("Messages in\nthe general chat".to_owned()),
+            importance: Importance::High,
+            conversation: Some(("chat".to_owned(), "general".to_owned())),
+            allow_bubbles: Some(true),
+        };
+
+        let json = serde_json::to_string(&vec![channel.clone()]).unwrap();
+        let read: Vec<OwnedNotificationChannel> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(read, vec![channel]);
+    }
+
+    #[test]
+    fn borrowed_channel_serializes_like_owned() {
+        let owned = OwnedNotificationChannel {
+            id: "alerts".to_owned(),
+            name: "Alerts".to_owned(),
+            desc: None,
+            importance: Importance::Default,
+            conversation: None,
+            allow_bubbles: None,
+        };
+
+        assert_eq!(
+            serde_json::to_value(owned.as_channel()).unwrap(),
+            serde_json::to_value(&owned).unwrap(),
+        );
+    }
 }
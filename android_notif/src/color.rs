@@ -0,0 +1,84 @@
+use jni::sys::jint;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseColorError(String);
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid color {:?}, expected #RRGGBB or #AARRGGBB",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseColorError {}
+
+/// Packs the channels into the ARGB `jint` taken by Android's color APIs.
+pub fn color_from_argb(a: u8, r: u8, g: u8, b: u8) -> jint {
+    u32::from_be_bytes([a, r, g, b]) as jint
+}
+
+/// Parses `#RRGGBB` (fully opaque) or `#AARRGGBB`.
+pub fn color_from_hex(s: &str) -> Result<jint, ParseColorError> {
+    let err = || ParseColorError(s.to_owned());
+
+    let hex = s.strip_prefix('#').ok_or_else(err)?;
+    if !hex.bytes().all(|x| x.is_ascii_hexdigit()) {
+        return Err(err());
+    }
+
+    let value = u32::from_str_radix(hex, 16).map_err(|_| err())?;
+
+    match hex.len() {
+        6 => Ok((0xff00_0000 | value) as jint),
+        8 => Ok(value as jint),
+        _ => Err(err()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argb_packs_big_endian() {
+        assert_eq!(
+            color_from_argb(0xff, 0x12, 0x34, 0x56),
+            0xff12_3456_u32 as jint
+        );
+        assert_eq!(color_from_argb(0, 0, 0, 0), 0);
+    }
+
+    #[test]
+    fn hex_parses_both_forms() {
+        assert_eq!(color_from_hex("#123456"), Ok(0xff12_3456_u32 as jint));
+        assert_eq!(color_from_hex("#80abcdef"), Ok(0x80ab_cdef_u32 as jint));
+        assert_eq!(color_from_hex("#ABCDEF"), color_from_hex("#abcdef"));
+    }
+
+    #[test]
+    fn hex_rejects_malformed_input() {
+        for s in [
+            "",
+            "#",
+            "123456",
+            "#12345",
+            "#1234567",
+            "#123456789",
+            "#12345g",
+            "#+12345",
+            "# 12345",
+        ] {
+            assert_eq!(
+                color_from_hex(s),
+                Err(ParseColorError(s.to_owned())),
+                "{:?}",
+                s
+            );
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use android_api_util::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum NotifError {
+    /// Reading the notification's state failed.
+    Android(Error),
+    /// Two settings were combined which Android silently resolves in favour of one.
+    Conflict {
+        first: &'static str,
+        second: &'static str,
+    },
+    /// A setting Android requires was left out.
+    Missing(&'static str),
+}
+
+impl fmt::Display for NotifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifError::Android(e) => write!(f, "{}", e),
+            NotifError::Conflict { first, second } => {
+                write!(f, "`{}` conflicts with `{}`", first, second)
+            }
+            NotifError::Missing(setting) => write!(f, "`{}` must be set", setting),
+        }
+    }
+}
+
+impl std::error::Error for NotifError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NotifError::Android(e) => Some(e),
+            NotifError::Conflict { .. } | NotifError::Missing(_) => None,
+        }
+    }
+}
+
+impl From<Error> for NotifError {
+    fn from(e: Error) -> Self {
+        NotifError::Android(e)
+    }
+}
+
+impl From<jni::errors::Error> for NotifError {
+    fn from(e: jni::errors::Error) -> Self {
+        NotifError::Android(e.into())
+    }
+}
@@ -0,0 +1,102 @@
+use jni::sys::jint;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Hands out notification ids so that unrelated parts of an app don't
+/// overwrite each other's notifications.
+pub struct NotificationIds {
+    next: AtomicI32,
+}
+
+impl NotificationIds {
+    pub const fn new(first: jint) -> Self {
+        Self {
+            next: AtomicI32::new(first),
+        }
+    }
+
+    /// Returns a fresh id, increasing with every call.
+    pub fn next(&self) -> jint {
+        self.next.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Hashes `key` to a positive id which is the same across runs,
+    /// so a notification can be updated or cancelled by key alone.
+    ///
+    /// Uses 32 bit FNV-1a.
+    pub fn stable(key: &str) -> jint {
+        let hash = key.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+        });
+
+        (hash & 0x7fff_ffff) as jint
+    }
+}
+
+impl Default for NotificationIds {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+/// A tag for the notifications of `entity`, such as a user or chat id, which is
+/// the same across runs so they can be updated or cancelled by entity alone.
+///
+/// Uses 64 bit FNV-1a, as tags have room for more bits than ids.
+pub fn tag_for(entity: &str) -> String {
+    let hash = entity
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_counts_up_from_first() {
+        let ids = NotificationIds::new(5);
+
+        assert_eq!(ids.next(), 5);
+        assert_eq!(ids.next(), 6);
+        assert_eq!(NotificationIds::default().next(), 1);
+    }
+
+    #[test]
+    fn next_wraps_instead_of_panicking() {
+        let ids = NotificationIds::new(jint::MAX);
+
+        assert_eq!(ids.next(), jint::MAX);
+        assert_eq!(ids.next(), jint::MIN);
+    }
+
+    #[test]
+    fn stable_matches_fnv1a_and_is_positive() {
+        assert_eq!(NotificationIds::stable(""), 0x011c_9dc5);
+        // FNV-1a of "a" is 0xe40c292c, with the sign bit cleared
+        assert_eq!(NotificationIds::stable("a"), 0x640c_292c);
+        assert_eq!(
+            NotificationIds::stable("chat"),
+            NotificationIds::stable("chat")
+        );
+        assert_ne!(
+            NotificationIds::stable("chat"),
+            NotificationIds::stable("chats")
+        );
+
+        for key in ["", "a", "chat", "user:42", "\u{1F600}"] {
+            assert!(NotificationIds::stable(key) >= 0);
+        }
+    }
+
+    #[test]
+    fn tag_for_matches_fnv1a_and_is_fixed_width() {
+        assert_eq!(tag_for(""), "cbf29ce484222325");
+        assert_eq!(tag_for("a"), "af63dc4c8601ec8c");
+        assert_eq!(tag_for("user:42"), "6c151ea4dcd221c2");
+        assert_ne!(tag_for("user:42"), tag_for("user:43"));
+    }
+}
@@ -1,2 +1,14 @@
+pub mod alerting;
+pub mod audio;
+pub mod call;
 pub mod channel;
+mod color;
+mod error;
+pub mod ids;
 pub mod notification;
+pub mod service;
+pub mod style;
+
+pub use color::{color_from_argb, color_from_hex, ParseColorError};
+pub use error::NotifError;
+pub use ids::tag_for;
@@ -1,10 +1,25 @@
-use crate::channel::NotificationChannelID;
-use android_api_util::CompatEnv;
-use jni::errors::Error;
-use jni::objects::{JObject, JValue};
+use crate::channel::{
+    channel_object, notification_channel_available, ChannelInfo, Importance, NotificationChannel,
+    NotificationChannelID,
+};
+use crate::service::ForegroundServiceBehavior;
+use crate::style::{BigTextStyle, NotificationStyle};
+use crate::NotifError;
+use android_api_util::bundle::Bundle;
+use android_api_util::intent::Intent;
+use android_api_util::resources::ResourceManager;
+use android_api_util::{runnable, CompatEnv, Error};
+use jni::errors::Error as JniError;
+use jni::objects::{GlobalRef, JObject, JValue};
 use jni::strings::JNIString;
-use jni::sys::jint;
+use jni::sys::{jint, jlong};
 use once_cell::sync::OnceCell;
+#[cfg(debug_assertions)]
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[rustfmt::skip]
 #[derive(Copy, Clone, Debug, PartialEq, Hash)]
@@ -26,7 +41,7 @@ pub struct ActivityFlags {
     pub launched_from_history:  jint,
     /// API 24: https://developer.android.com/reference/android/content/Intent#FLAG_ACTIVITY_LAUNCH_ADJACENT
     pub launch_adjacent:        Option<jint>,
-    /// API 28: https://developer.android.com/reference/android/content/Intent#FLAG_ACTIVITY_LAUNCH_ADJACENT
+    /// API 28: https://developer.android.com/reference/android/content/Intent#FLAG_ACTIVITY_MATCH_EXTERNAL
     pub match_external:         Option<jint>,
     /// API 1: https://developer.android.com/reference/android/content/Intent#FLAG_ACTIVITY_MULTIPLE_TASK
     pub multiple_task:          jint,
@@ -53,11 +68,44 @@ pub struct ActivityFlags {
     /// API 21: https://developer.android.com/reference/android/content/Intent#FLAG_ACTIVITY_RETAIN_IN_RECENTS
     pub retain_in_recents:      Option<jint>,
     /// API 1: https://developer.android.com/reference/android/content/Intent#FLAG_ACTIVITY_SINGLE_TOP
-    pub single_top:             Option<jint>,
+    pub single_top:             jint,
     /// API 11: https://developer.android.com/reference/android/content/Intent#FLAG_ACTIVITY_TASK_ON_HOME
     pub task_on_home:           Option<jint>,
 }
 
+impl ActivityFlags {
+    /// Every flag's constant name paired with its value, or `None` where the
+    /// device's API level doesn't have it.
+    #[rustfmt::skip]
+    pub fn as_map(&self) -> Vec<(&'static str, Option<jint>)> {
+        vec![
+            ("FLAG_ACTIVITY_BROUGHT_TO_FRONT",      Some(self.brought_to_front)),
+            ("FLAG_ACTIVITY_CLEAR_TASK",            self.clear_task),
+            ("FLAG_ACTIVITY_CLEAR_TOP",             Some(self.clear_top)),
+            ("FLAG_ACTIVITY_CLEAR_WHEN_TASK_RESET", self.clear_when_task_reset),
+            ("FLAG_ACTIVITY_EXCLUDE_FROM_RECENTS",  Some(self.exclude_from_recents)),
+            ("FLAG_ACTIVITY_FORWARD_RESULT",        Some(self.forward_result)),
+            ("FLAG_ACTIVITY_LAUNCHED_FROM_HISTORY", Some(self.launched_from_history)),
+            ("FLAG_ACTIVITY_LAUNCH_ADJACENT",       self.launch_adjacent),
+            ("FLAG_ACTIVITY_MATCH_EXTERNAL",        self.match_external),
+            ("FLAG_ACTIVITY_MULTIPLE_TASK",         Some(self.multiple_task)),
+            ("FLAG_ACTIVITY_NEW_DOCUMENT",          self.new_document),
+            ("FLAG_ACTIVITY_NEW_TASK",              Some(self.new_task)),
+            ("FLAG_ACTIVITY_NO_ANIMATION",          self.no_animation),
+            ("FLAG_ACTIVITY_NO_HISTORY",            Some(self.no_history)),
+            ("FLAG_ACTIVITY_NO_USER_ACTION",        self.no_user_action),
+            ("FLAG_ACTIVITY_PREVIOUS_IS_TOP",       Some(self.previous_is_top)),
+            ("FLAG_ACTIVITY_REORDER_TO_FRONT",      self.reorder_to_front),
+            ("FLAG_ACTIVITY_REQUIRE_DEFAULT",       self.require_default),
+            ("FLAG_ACTIVITY_REQUIRE_NON_BROWSER",   self.require_non_browser),
+            ("FLAG_ACTIVITY_RESET_TASK_IF_NEEDED",  Some(self.reset_task_if_needed)),
+            ("FLAG_ACTIVITY_RETAIN_IN_RECENTS",     self.retain_in_recents),
+            ("FLAG_ACTIVITY_SINGLE_TOP",            Some(self.single_top)),
+            ("FLAG_ACTIVITY_TASK_ON_HOME",          self.task_on_home),
+        ]
+    }
+}
+
 struct ActivityFlagLoader<'a>(CompatEnv<'a>);
 
 impl<'a> ActivityFlagLoader<'a> {
@@ -67,17 +115,25 @@ impl<'a> ActivityFlagLoader<'a> {
         // API 1: https://developer.android.com/reference/android/content/Intent
         let intent = env.find_class("android/content/Intent")?;
 
-        let load = |name: &str| -> Result<Option<jint>, Error> {
-            env.try_get_static_field(intent, name, "I")
-                .transpose()
-                .map(|x| x.and_then(|x| x.i()))
-                .transpose()
-        };
+        Self::from_lookup(|name| {
+            let value = env.try_get_static_field(intent, name, "I")?;
+
+            Ok(value.map(|x| x.i()).transpose()?)
+        })
+    }
 
+    /// Fills in each field from the `Intent` constant of the same name, looked up
+    /// through `load`, which returns `None` for constants the device doesn't have.
+    fn from_lookup(
+        load: impl Fn(&str) -> Result<Option<jint>, Error>,
+    ) -> Result<ActivityFlags, Error> {
         let load_yes = |name: &str| -> Result<jint, Error> {
-            load(name)?.ok_or_else(|| Error::FieldNotFound {
-                sig: "I".into(),
-                name: name.into(),
+            load(name)?.ok_or_else(|| {
+                JniError::FieldNotFound {
+                    sig: "I".into(),
+                    name: name.into(),
+                }
+                .into()
             })
         };
 
@@ -103,7 +159,7 @@ impl<'a> ActivityFlagLoader<'a> {
             require_non_browser: load("FLAG_ACTIVITY_REQUIRE_NON_BROWSER")?,
             reset_task_if_needed: load_yes("FLAG_ACTIVITY_RESET_TASK_IF_NEEDED")?,
             retain_in_recents: load("FLAG_ACTIVITY_RETAIN_IN_RECENTS")?,
-            single_top: load("FLAG_ACTIVITY_SINGLE_TOP")?,
+            single_top: load_yes("FLAG_ACTIVITY_SINGLE_TOP")?,
             task_on_home: load("FLAG_ACTIVITY_TASK_ON_HOME")?,
         };
 
@@ -111,43 +167,222 @@ impl<'a> ActivityFlagLoader<'a> {
     }
 }
 
+static ACTIVITY_FLAGS: OnceCell<ActivityFlags> = OnceCell::new();
+
 /// Supports API 1
 ///
 /// API 30
 pub fn activity_flags(env: CompatEnv<'_>) -> &'static ActivityFlags {
-    static FLAGS: OnceCell<ActivityFlags> = OnceCell::new();
+    ACTIVITY_FLAGS.get_or_init(move || ActivityFlagLoader(env).load().unwrap())
+}
+
+/// The table loaded by an earlier `activity_flags` call, or `None` if it hasn't
+/// been loaded yet.
+pub fn activity_flags_cached() -> Option<&'static ActivityFlags> {
+    ACTIVITY_FLAGS.get()
+}
+
+/// The flags of a built notification, decoded from `Notification.flags`.
+///
+/// The flag values are fixed by the SDK, so this works without an env. Flags which
+/// don't exist on the device's API level are ignored by it.
+///
+/// The `FLAG_*` values themselves are the associated consts, such as for `set_flag`.
+#[rustfmt::skip]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NotificationFlags {
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_AUTO_CANCEL
+    pub auto_cancel:        bool,
+    /// API 29: https://developer.android.com/reference/android/app/Notification#FLAG_BUBBLE
+    pub bubble:             bool,
+    /// API 5: https://developer.android.com/reference/android/app/Notification#FLAG_FOREGROUND_SERVICE
+    pub foreground_service: bool,
+    /// API 20: https://developer.android.com/reference/android/app/Notification#FLAG_GROUP_SUMMARY
+    pub group_summary:      bool,
+    /// API 11: https://developer.android.com/reference/android/app/Notification#FLAG_HIGH_PRIORITY
+    /// DEPRECATED in API 16
+    pub high_priority:      bool,
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_INSISTENT
+    pub insistent:          bool,
+    /// API 20: https://developer.android.com/reference/android/app/Notification#FLAG_LOCAL_ONLY
+    pub local_only:         bool,
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_NO_CLEAR
+    pub no_clear:           bool,
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_ONGOING_EVENT
+    pub ongoing:            bool,
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_ONLY_ALERT_ONCE
+    pub only_alert_once:    bool,
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_SHOW_LIGHTS
+    /// DEPRECATED in API 26
+    pub show_lights:        bool,
+}
+
+impl NotificationFlags {
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_SHOW_LIGHTS
+    /// DEPRECATED in API 26
+    pub const SHOW_LIGHTS: jint = 0x0000_0001;
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_ONGOING_EVENT
+    pub const ONGOING_EVENT: jint = 0x0000_0002;
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_INSISTENT
+    pub const INSISTENT: jint = 0x0000_0004;
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_ONLY_ALERT_ONCE
+    pub const ONLY_ALERT_ONCE: jint = 0x0000_0008;
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_AUTO_CANCEL
+    pub const AUTO_CANCEL: jint = 0x0000_0010;
+    /// API 1: https://developer.android.com/reference/android/app/Notification#FLAG_NO_CLEAR
+    pub const NO_CLEAR: jint = 0x0000_0020;
+    /// API 5: https://developer.android.com/reference/android/app/Notification#FLAG_FOREGROUND_SERVICE
+    pub const FOREGROUND_SERVICE: jint = 0x0000_0040;
+    /// API 11: https://developer.android.com/reference/android/app/Notification#FLAG_HIGH_PRIORITY
+    /// DEPRECATED in API 16
+    pub const HIGH_PRIORITY: jint = 0x0000_0080;
+    /// API 20: https://developer.android.com/reference/android/app/Notification#FLAG_LOCAL_ONLY
+    pub const LOCAL_ONLY: jint = 0x0000_0100;
+    /// API 20: https://developer.android.com/reference/android/app/Notification#FLAG_GROUP_SUMMARY
+    pub const GROUP_SUMMARY: jint = 0x0000_0200;
+    /// API 29: https://developer.android.com/reference/android/app/Notification#FLAG_BUBBLE
+    pub const BUBBLE: jint = 0x0000_1000;
+
+    /// Every field paired with its flag.
+    #[rustfmt::skip]
+    fn bits(&self) -> [(bool, jint); 11] {
+        [
+            (self.auto_cancel,        Self::AUTO_CANCEL),
+            (self.bubble,             Self::BUBBLE),
+            (self.foreground_service, Self::FOREGROUND_SERVICE),
+            (self.group_summary,      Self::GROUP_SUMMARY),
+            (self.high_priority,      Self::HIGH_PRIORITY),
+            (self.insistent,          Self::INSISTENT),
+            (self.local_only,         Self::LOCAL_ONLY),
+            (self.no_clear,           Self::NO_CLEAR),
+            (self.ongoing,            Self::ONGOING_EVENT),
+            (self.only_alert_once,    Self::ONLY_ALERT_ONCE),
+            (self.show_lights,        Self::SHOW_LIGHTS),
+        ]
+    }
+
+    /// Bits which aren't one of the flags above are dropped.
+    pub fn from_jint(flags: jint) -> Self {
+        let has = |flag: jint| flags & flag != 0;
+
+        Self {
+            auto_cancel: has(Self::AUTO_CANCEL),
+            bubble: has(Self::BUBBLE),
+            foreground_service: has(Self::FOREGROUND_SERVICE),
+            group_summary: has(Self::GROUP_SUMMARY),
+            high_priority: has(Self::HIGH_PRIORITY),
+            insistent: has(Self::INSISTENT),
+            local_only: has(Self::LOCAL_ONLY),
+            no_clear: has(Self::NO_CLEAR),
+            ongoing: has(Self::ONGOING_EVENT),
+            only_alert_once: has(Self::ONLY_ALERT_ONCE),
+            show_lights: has(Self::SHOW_LIGHTS),
+        }
+    }
+
+    pub fn to_jint(&self) -> jint {
+        self.bits()
+            .iter()
+            .filter(|(set, _)| *set)
+            .fold(0, |flags, (_, flag)| flags | flag)
+    }
+}
+
+#[rustfmt::skip]
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub struct ExtraKeys {
+    /// API 21: https://developer.android.com/reference/android/app/Notification#EXTRA_BIG_TEXT
+    pub big_text:     Option<String>,
+    /// API 26: https://developer.android.com/reference/android/app/Notification#EXTRA_COLORIZED
+    pub colorized:    Option<String>,
+    /// API 19: https://developer.android.com/reference/android/app/Notification#EXTRA_INFO_TEXT
+    pub info_text:    Option<String>,
+    /// API 19: https://developer.android.com/reference/android/app/Notification#EXTRA_SUB_TEXT
+    pub sub_text:     Option<String>,
+    /// API 19: https://developer.android.com/reference/android/app/Notification#EXTRA_SUMMARY_TEXT
+    pub summary_text: Option<String>,
+    /// API 19: https://developer.android.com/reference/android/app/Notification#EXTRA_TEXT
+    pub text:         Option<String>,
+    /// API 21: https://developer.android.com/reference/android/app/Notification#EXTRA_TEXT_LINES
+    pub text_lines:   Option<String>,
+    /// API 19: https://developer.android.com/reference/android/app/Notification#EXTRA_TITLE
+    pub title:        Option<String>,
+    /// API 19: https://developer.android.com/reference/android/app/Notification#EXTRA_TITLE_BIG
+    pub title_big:    Option<String>,
+}
+
+struct ExtraKeyLoader<'a>(CompatEnv<'a>);
+
+impl<'a> ExtraKeyLoader<'a> {
+    pub fn load(&self) -> Result<ExtraKeys, Error> {
+        let env = self.0;
+
+        // API 1: https://developer.android.com/reference/android/app/Notification
+        let notification = env.find_class("android/app/Notification")?;
+
+        let load = |name: &str| -> Result<Option<String>, Error> {
+            let value = env.try_get_static_field(notification, name, "Ljava/lang/String;")?;
+
+            value
+                .map(|x| Ok(env.get_string(x.l()?.into())?.into()))
+                .transpose()
+        };
+
+        let value = ExtraKeys {
+            big_text: load("EXTRA_BIG_TEXT")?,
+            colorized: load("EXTRA_COLORIZED")?,
+            info_text: load("EXTRA_INFO_TEXT")?,
+            sub_text: load("EXTRA_SUB_TEXT")?,
+            summary_text: load("EXTRA_SUMMARY_TEXT")?,
+            text: load("EXTRA_TEXT")?,
+            text_lines: load("EXTRA_TEXT_LINES")?,
+            title: load("EXTRA_TITLE")?,
+            title_big: load("EXTRA_TITLE_BIG")?,
+        };
+
+        Ok(value)
+    }
+}
+
+/// Supports API 1
+///
+/// API 26
+///
+/// The keys of a `Notification`'s `extras`, which only exist from API 19.
+pub fn extra_keys(env: CompatEnv<'_>) -> &'static ExtraKeys {
+    static KEYS: OnceCell<ExtraKeys> = OnceCell::new();
 
-    FLAGS.get_or_init(move || ActivityFlagLoader(env).load().unwrap())
+    KEYS.get_or_init(move || ExtraKeyLoader(env).load().unwrap())
 }
 
 /// API 1
 pub fn create_intent(env: CompatEnv<'_>, flags: jint) -> Result<JObject<'_>, Error> {
-    // API 1: https://developer.android.com/reference/android/content/Intent
-    let class = env.find_class("android/content/Intent")?;
-
-    // API 1: https://developer.android.com/reference/android/content/Intent#Intent(android.content.Context,%20java.lang.Class%3C?%3E)
-    let intent = env.new_object(
-        class,
-        "(Landroid/content/Context;Ljava/lang/Class;)V",
-        &[
-            JValue::Object(env.context),
-            JValue::Object(*env.get_object_class(env.context)?),
-        ],
-    )?;
-
-    // API 1: https://developer.android.com/reference/android/content/Intent#setFlags(int)
-    env.call_method(
-        intent,
-        "setFlags",
-        "(I)Landroid/content/Intent;",
-        &[JValue::Int(flags)],
-    )?;
-
-    Ok(intent)
+    Ok(create_activity_intent_builder(env, flags)?.build())
+}
+
+/// API 1
+///
+/// An explicit intent for the current activity with `flags`, to which extras
+/// can still be added before passing it to `pending_intent`.
+pub fn create_activity_intent_builder(
+    env: CompatEnv<'_>,
+    flags: jint,
+) -> Result<Intent<'_>, Error> {
+    Intent::for_context_class(env)?.set_flags(flags)
 }
 
 /// API 1
 pub fn pending_intent<'a>(env: CompatEnv<'a>, intent: JObject<'_>) -> Result<JObject<'a>, Error> {
+    pending_intent_with_flags(env, intent, 0, 0)
+}
+
+/// API 1
+pub fn pending_intent_with_flags<'a>(
+    env: CompatEnv<'a>,
+    intent: JObject<'_>,
+    request_code: jint,
+    flags: jint,
+) -> Result<JObject<'a>, Error> {
     // API 1: https://developer.android.com/reference/android/app/PendingIntent
     let class = env.find_class("android/app/PendingIntent")?;
     // API 1: https://developer.android.com/reference/android/app/PendingIntent#getActivity(android.content.Context,%20int,%20android.content.Intent,%20int)
@@ -158,9 +393,9 @@ pub fn pending_intent<'a>(env: CompatEnv<'a>, intent: JObject<'_>) -> Result<JOb
             "(Landroid/content/Context;ILandroid/content/Intent;I)Landroid/app/PendingIntent;",
             &[
                 JValue::Object(env.context),
-                JValue::Int(0),
+                JValue::Int(request_code),
                 JValue::Object(intent),
-                JValue::Int(0),
+                JValue::Int(flags),
             ],
         )?
         .l()?;
@@ -168,9 +403,328 @@ pub fn pending_intent<'a>(env: CompatEnv<'a>, intent: JObject<'_>) -> Result<JOb
     Ok(value)
 }
 
+/// Supports API 1
+///
+/// API 31
+///
+/// A `PendingIntent` which opens the current activity with `flags` and `extras`,
+/// as a notification's content intent needs. It's immutable where the device
+/// supports it, and replaces the extras of an earlier one with the same `request_code`.
+pub fn pending_activity_intent<'a>(
+    env: CompatEnv<'a>,
+    flags: jint,
+    request_code: jint,
+    extras: Option<&Bundle<'_>>,
+) -> Result<JObject<'a>, NotifError> {
+    let intent = create_activity_intent_builder(env, flags)?;
+
+    if let Some(extras) = extras {
+        intent.put_extras(extras)?;
+    }
+
+    immutable_pending_intent(env, &intent, request_code)
+}
+
+/// Supports API 1
+///
+/// API 31
+fn immutable_pending_intent<'a>(
+    env: CompatEnv<'a>,
+    intent: &Intent<'_>,
+    request_code: jint,
+) -> Result<JObject<'a>, NotifError> {
+    let pending_flags = PendingIntentFlags::new()
+        .immutable()
+        .update_current()
+        .build(env)?;
+
+    Ok(pending_intent_with_flags(
+        env,
+        intent.build(),
+        request_code,
+        pending_flags,
+    )?)
+}
+
+/// Builds the flags passed to `pending_intent_with_flags`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PendingIntentFlags {
+    immutable: bool,
+    mutable: bool,
+    update_current: bool,
+    cancel_current: bool,
+    one_shot: bool,
+    no_create: bool,
+}
+
+impl PendingIntentFlags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// API 23: https://developer.android.com/reference/android/app/PendingIntent#FLAG_IMMUTABLE
+    pub fn immutable(self) -> Self {
+        Self {
+            immutable: true,
+            ..self
+        }
+    }
+
+    /// API 31: https://developer.android.com/reference/android/app/PendingIntent#FLAG_MUTABLE
+    pub fn mutable(self) -> Self {
+        Self {
+            mutable: true,
+            ..self
+        }
+    }
+
+    /// API 3: https://developer.android.com/reference/android/app/PendingIntent#FLAG_UPDATE_CURRENT
+    pub fn update_current(self) -> Self {
+        Self {
+            update_current: true,
+            ..self
+        }
+    }
+
+    /// API 1: https://developer.android.com/reference/android/app/PendingIntent#FLAG_CANCEL_CURRENT
+    pub fn cancel_current(self) -> Self {
+        Self {
+            cancel_current: true,
+            ..self
+        }
+    }
+
+    /// API 1: https://developer.android.com/reference/android/app/PendingIntent#FLAG_ONE_SHOT
+    pub fn one_shot(self) -> Self {
+        Self {
+            one_shot: true,
+            ..self
+        }
+    }
+
+    /// API 1: https://developer.android.com/reference/android/app/PendingIntent#FLAG_NO_CREATE
+    pub fn no_create(self) -> Self {
+        Self {
+            no_create: true,
+            ..self
+        }
+    }
+
+    /// Supports API 1
+    ///
+    /// API 31
+    ///
+    /// Flags which don't exist on the device are left out. From API 31 exactly
+    /// one of `immutable` and `mutable` must be set, as Android throws otherwise.
+    pub fn build(&self, env: CompatEnv<'_>) -> Result<jint, NotifError> {
+        // API 1: https://developer.android.com/reference/android/app/PendingIntent
+        let class = env.find_class("android/app/PendingIntent")?;
+
+        self.compose(|name| {
+            let value = env.try_get_static_field(class, name, "I")?;
+
+            Ok(value.map(|x| x.i()).transpose()?)
+        })
+    }
+
+    /// Combines the set flags, looking each value up by field name through `load`,
+    /// which returns `None` for flags the device doesn't have.
+    fn compose(
+        &self,
+        mut load: impl FnMut(&str) -> Result<Option<jint>, Error>,
+    ) -> Result<jint, NotifError> {
+        let mutable = load("FLAG_MUTABLE")?;
+        if mutable.is_some() {
+            match (self.immutable, self.mutable) {
+                (true, true) => {
+                    return Err(NotifError::Conflict {
+                        first: "immutable",
+                        second: "mutable",
+                    })
+                }
+                (false, false) => return Err(NotifError::Missing("immutable or mutable")),
+                _ => {}
+            }
+        }
+
+        let flags = [
+            (self.immutable, load("FLAG_IMMUTABLE")?),
+            (self.mutable, mutable),
+            (self.update_current, load("FLAG_UPDATE_CURRENT")?),
+            (self.cancel_current, load("FLAG_CANCEL_CURRENT")?),
+            (self.one_shot, load("FLAG_ONE_SHOT")?),
+            (self.no_create, load("FLAG_NO_CREATE")?),
+        ];
+
+        Ok(flags
+            .iter()
+            .filter(|(set, _)| *set)
+            .filter_map(|(_, value)| *value)
+            .fold(0, |flags, value| flags | value))
+    }
+}
+
+/// The ranking of a notification below API 26, where channels' `Importance` replaces it.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Priority {
+    /// API 16: https://developer.android.com/reference/android/app/Notification#PRIORITY_DEFAULT
+    Default,
+    /// API 16: https://developer.android.com/reference/android/app/Notification#PRIORITY_HIGH
+    High,
+    /// API 16: https://developer.android.com/reference/android/app/Notification#PRIORITY_LOW
+    Low,
+    /// API 16: https://developer.android.com/reference/android/app/Notification#PRIORITY_MAX
+    Max,
+    /// API 16: https://developer.android.com/reference/android/app/Notification#PRIORITY_MIN
+    Min,
+}
+
+impl Priority {
+    fn internal_name(&self) -> &'static str {
+        match self {
+            Priority::Default => "PRIORITY_DEFAULT",
+            Priority::High => "PRIORITY_HIGH",
+            Priority::Low => "PRIORITY_LOW",
+            Priority::Max => "PRIORITY_MAX",
+            Priority::Min => "PRIORITY_MIN",
+        }
+    }
+
+    /// Supports API 1
+    ///
+    /// API 16
+    ///
+    /// DEPRECATED in API 26
+    pub fn internal_value(&self, env: CompatEnv<'_>) -> Result<jint, Error> {
+        // API 1: https://developer.android.com/reference/android/app/Notification
+        let class = env.find_class("android/app/Notification")?;
+        // API 16 as per all possible fields for Self, priorities are unused below it
+        let value = env.try_get_static_field(class, self.internal_name(), "I")?;
+
+        Ok(value.map(|x| x.i()).transpose()?.unwrap_or(0))
+    }
+
+    /// The channel importance which behaves like this priority did before API 26.
+    pub fn importance(&self) -> Importance {
+        match self {
+            Priority::Default => Importance::Default,
+            Priority::High => Importance::High,
+            Priority::Low => Importance::Low,
+            Priority::Max => Importance::Max,
+            Priority::Min => Importance::Min,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum BadgeIconType {
+    /// API 26: https://developer.android.com/reference/android/app/Notification#BADGE_ICON_NONE
+    None,
+    /// API 26: https://developer.android.com/reference/android/app/Notification#BADGE_ICON_SMALL
+    Small,
+    /// API 26: https://developer.android.com/reference/android/app/Notification#BADGE_ICON_LARGE
+    Large,
+}
+
+impl BadgeIconType {
+    fn internal_name(&self) -> &'static str {
+        match self {
+            BadgeIconType::None => "BADGE_ICON_NONE",
+            BadgeIconType::Small => "BADGE_ICON_SMALL",
+            BadgeIconType::Large => "BADGE_ICON_LARGE",
+        }
+    }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    pub fn internal_value(&self, env: CompatEnv<'_>) -> Result<jint, Error> {
+        // API 1: https://developer.android.com/reference/android/app/Notification
+        let class = env.find_class("android/app/Notification")?;
+        // API 26 as per all possible fields for Self, badges are unused below it
+        let value = env.try_get_static_field(class, self.internal_name(), "I")?;
+
+        Ok(value.map(|x| x.i()).transpose()?.unwrap_or(0))
+    }
+}
+
+/// What a built notification would display, as read by `NotificationBuilder::build_and_read`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotificationSnapshot {
+    /// API 19: https://developer.android.com/reference/android/app/Notification#EXTRA_TITLE
+    pub title: Option<String>,
+    /// API 19: https://developer.android.com/reference/android/app/Notification#EXTRA_TEXT
+    pub text: Option<String>,
+    /// API 1: https://developer.android.com/reference/android/app/Notification#icon
+    pub small_icon: jint,
+    /// API 26: https://developer.android.com/reference/android/app/Notification#getChannelId()
+    pub channel_id: Option<String>,
+}
+
+impl NotificationSnapshot {
+    /// Supports API 1
+    ///
+    /// API 26
+    ///
+    /// Reads back what `notif`, a built `Notification`, would display.
+    pub(crate) fn read(
+        env: CompatEnv<'_>,
+        notif: JObject<'_>,
+    ) -> Result<NotificationSnapshot, Error> {
+        let keys = extra_keys(env);
+
+        // API 19: https://developer.android.com/reference/android/app/Notification#extras
+        let extras = env
+            .try_get_field(notif, "extras", "Landroid/os/Bundle;")?
+            .map(|x| x.l())
+            .transpose()?;
+
+        let read_extra = |key: &Option<String>| -> Result<Option<String>, Error> {
+            let (extras, key) = match (extras, key) {
+                (Some(x), Some(key)) if !x.is_null() => (x, key),
+                _ => return Ok(None),
+            };
+
+            // API 12: https://developer.android.com/reference/android/os/Bundle#getCharSequence(java.lang.String)
+            let value = env
+                .call_method(
+                    extras,
+                    "getCharSequence",
+                    "(Ljava/lang/String;)Ljava/lang/CharSequence;",
+                    &[JValue::Object(*env.new_string(key)?)],
+                )?
+                .l()?;
+
+            if value.is_null() {
+                Ok(None)
+            } else {
+                Ok(Some(env.char_sequence_to_string(value)?))
+            }
+        };
+
+        // API 26: https://developer.android.com/reference/android/app/Notification#getChannelId()
+        let channel_id = env
+            .try_call_method(notif, "getChannelId", "()Ljava/lang/String;", &[])?
+            .map(|x| x.l())
+            .transpose()?
+            .filter(|x| !x.is_null())
+            .map(|x| env.char_sequence_to_string(x))
+            .transpose()?;
+
+        Ok(NotificationSnapshot {
+            title: read_extra(&keys.title)?,
+            text: read_extra(&keys.text)?,
+            // API 1: https://developer.android.com/reference/android/app/Notification#icon
+            // DEPRECATED in API 26, but still set by `setSmallIcon(int)`
+            small_icon: env.get_field(notif, "icon", "I")?.i()?,
+            channel_id,
+        })
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct NotificationBuilder<'a> {
-    internal: JObject<'a>,
+    pub(crate) internal: JObject<'a>,
     env: CompatEnv<'a>,
 }
 
@@ -180,27 +734,40 @@ impl<'a> NotificationBuilder<'a> {
     /// API 26
     pub fn new(env: CompatEnv<'a>, channel_id: NotificationChannelID<'_>) -> Result<Self, Error> {
         // API 11: https://developer.android.com/reference/android/app/Notification.Builder
-        let class = env.find_class("android/app/Notification$Builder")?;
+        let class = match env.try_find_class("android/app/Notification$Builder")? {
+            Some(x) => x,
+            None => return Err(Error::Unsupported { api_required: 11 }),
+        };
+
+        // API 26: https://developer.android.com/reference/android/app/Notification.Builder#Builder(android.content.Context,%20java.lang.String)
+        #[cfg(feature = "min_sdk_26")]
+        let builder = env.new_object(
+            class,
+            "(Landroid/content/Context;Ljava/lang/String;)V",
+            &[
+                JValue::Object(env.context),
+                JValue::Object(*env.new_string(channel_id)?),
+            ],
+        )?;
 
         // API 26: https://developer.android.com/reference/android/app/Notification.Builder#Builder(android.content.Context,%20java.lang.String)
         // Fallback API 11: https://developer.android.com/reference/android/app/Notification.Builder#Builder(android.content.Context)
-        let builder = env
-            .try_new_object(
-                class,
-                "(Landroid/content/Context;Ljava/lang/String;)V",
-                &[
-                    JValue::Object(env.context),
-                    JValue::Object(*env.new_string(channel_id)?),
-                ],
-            )
-            .transpose()
-            .unwrap_or_else(|| {
+        #[cfg(not(feature = "min_sdk_26"))]
+        let builder = env.new_object_or(
+            class,
+            "(Landroid/content/Context;Ljava/lang/String;)V",
+            &[
+                JValue::Object(env.context),
+                JValue::Object(*env.new_string(channel_id)?),
+            ],
+            || {
                 env.new_object(
                     class,
                     "(Landroid/content/Context;)V",
                     &[JValue::Object(env.context)],
                 )
-            })?;
+            },
+        )?;
 
         Ok(Self {
             internal: builder,
@@ -208,6 +775,46 @@ impl<'a> NotificationBuilder<'a> {
         })
     }
 
+    /// API 24
+    ///
+    /// Recovers a builder for `notif`, such as one listed by `getActiveNotifications`,
+    /// so it can be changed and posted again.
+    pub fn from_existing(env: CompatEnv<'a>, notif: JObject<'_>) -> Result<Self, Error> {
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder
+        let class = env.find_class("android/app/Notification$Builder")?;
+
+        // API 24: https://developer.android.com/reference/android/app/Notification.Builder#recoverBuilder(android.content.Context,%20android.app.Notification)
+        let builder = env.try_call_static_method(
+            class,
+            "recoverBuilder",
+            "(Landroid/content/Context;Landroid/app/Notification;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(env.context), JValue::Object(notif)],
+        )?;
+
+        match builder {
+            Some(x) => Ok(Self {
+                internal: x.l()?,
+                env,
+            }),
+            None => Err(Error::Unsupported { api_required: 24 }),
+        }
+    }
+
+    /// Supports API 11
+    ///
+    /// API 26
+    pub fn set_channel_id(&self, channel_id: NotificationChannelID<'_>) -> Result<Self, Error> {
+        // API 26: https://developer.android.com/reference/android/app/Notification.Builder#setChannelId(java.lang.String)
+        self.env.try_call_method(
+            self.internal,
+            "setChannelId",
+            "(Ljava/lang/String;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(*self.env.new_string(channel_id)?)],
+        )?;
+
+        Ok(*self)
+    }
+
     /// API 11
     pub fn set_intent(&self, intent: JObject<'_>) -> Result<Self, Error> {
         // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setContentIntent(android.app.PendingIntent)
@@ -221,6 +828,110 @@ impl<'a> NotificationBuilder<'a> {
         Ok(*self)
     }
 
+    /// Supports API 11
+    ///
+    /// API 31
+    ///
+    /// Opens the current activity when the notification is tapped, with the intent
+    /// first passed to `configure` to add extras or flags.
+    ///
+    /// Pending intents with the same `request_code` are shared, and the newest extras
+    /// replace the older ones, so give each notification its own, such as its id.
+    pub fn with_tap_action(
+        &self,
+        request_code: jint,
+        configure: impl FnOnce(&Intent<'a>) -> Result<(), Error>,
+    ) -> Result<Self, NotifError> {
+        let intent = create_activity_intent_builder(self.env, 0)?;
+        configure(&intent)?;
+
+        let pending = immutable_pending_intent(self.env, &intent, request_code)?;
+
+        Ok(self.set_intent(pending)?)
+    }
+
+    /// API 11
+    ///
+    /// Launches `intent`, a `PendingIntent`, instead of only showing the notification,
+    /// such as for an incoming call while the device is locked.
+    pub fn set_full_screen_intent(
+        &self,
+        intent: JObject<'_>,
+        high_priority: bool,
+    ) -> Result<Self, Error> {
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setFullScreenIntent(android.app.PendingIntent,%20boolean)
+        self.env.call_method(
+            self.internal,
+            "setFullScreenIntent",
+            "(Landroid/app/PendingIntent;Z)Landroid/app/Notification$Builder;",
+            &[JValue::Object(intent), JValue::Bool(high_priority as u8)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 21
+    ///
+    /// `category` is the value of one of the `Notification.CATEGORY_*` constants, such as `"call"`.
+    pub fn set_category(&self, category: &str) -> Result<Self, Error> {
+        // API 21: https://developer.android.com/reference/android/app/Notification.Builder#setCategory(java.lang.String)
+        self.env.try_call_method(
+            self.internal,
+            "setCategory",
+            "(Ljava/lang/String;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(*self.env.new_string(category)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 16
+    ///
+    /// DEPRECATED in API 26
+    pub fn set_priority(&self, priority: Priority) -> Result<Self, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.Builder#setPriority(int)
+        self.env.try_call_method(
+            self.internal,
+            "setPriority",
+            "(I)Landroid/app/Notification$Builder;",
+            &[JValue::Int(priority.internal_value(self.env)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 26
+    ///
+    /// Sets the count and icon shown on the app's launcher badge. Whether and how
+    /// badges appear is up to the launcher, many ignore the count or show no badge
+    /// at all. Below API 26 only the count is set, which older versions show in
+    /// the notification itself.
+    pub fn set_badge(&self, count: jint, icon_type: BadgeIconType) -> Result<Self, Error> {
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setNumber(int)
+        self.env.call_method(
+            self.internal,
+            "setNumber",
+            "(I)Landroid/app/Notification$Builder;",
+            &[JValue::Int(count)],
+        )?;
+
+        // API 26: https://developer.android.com/reference/android/app/Notification.Builder#setBadgeIconType(int)
+        self.env.try_call_method(
+            self.internal,
+            "setBadgeIconType",
+            "(I)Landroid/app/Notification$Builder;",
+            &[JValue::Int(icon_type.internal_value(self.env)?)],
+        )?;
+
+        Ok(*self)
+    }
+
     /// API 11
     pub fn set_title(&self, title: impl Into<JNIString>) -> Result<Self, Error> {
         // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setContentTitle(java.lang.CharSequence)
@@ -248,26 +959,30 @@ impl<'a> NotificationBuilder<'a> {
     }
 
     /// API 11
-    pub fn set_auto_cancel(&self, auto_cancel: bool) -> Result<Self, Error> {
-        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setAutoCancel(boolean)
+    ///
+    /// Sets a styled title, such as one from `html_to_charsequence`.
+    pub fn set_title_charsequence(&self, title: JObject<'_>) -> Result<Self, Error> {
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setContentTitle(java.lang.CharSequence)
         self.env.call_method(
             self.internal,
-            "setAutoCancel",
-            "(Z)Landroid/app/Notification$Builder;",
-            &[JValue::Bool(auto_cancel as u8)],
+            "setContentTitle",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(title)],
         )?;
 
         Ok(*self)
     }
 
     /// API 11
-    pub fn set_small_icon(&self, icon: jint) -> Result<Self, Error> {
-        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setSmallIcon(int)
+    ///
+    /// Sets styled content text, such as one from `html_to_charsequence`.
+    pub fn set_content_text_charsequence(&self, content: JObject<'_>) -> Result<Self, Error> {
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setContentText(java.lang.CharSequence)
         self.env.call_method(
             self.internal,
-            "setSmallIcon",
-            "(I)Landroid/app/Notification$Builder;",
-            &[JValue::Int(icon)],
+            "setContentText",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(content)],
         )?;
 
         Ok(*self)
@@ -276,75 +991,1805 @@ impl<'a> NotificationBuilder<'a> {
     /// Supports API 11
     ///
     /// API 16
-    fn build(&self) -> Result<JObject<'a>, Error> {
-        // API 16: https://developer.android.com/reference/android/app/Notification.Builder#build()
-        // Fallback API 11: https://developer.android.com/reference/android/app/Notification.Builder#getNotification()
-        let x = self
-            .env
-            .try_call_method(self.internal, "build", "()Landroid/app/Notification;", &[])
-            .transpose()
-            .unwrap_or_else(|| {
-                self.env.call_method(
-                    self.internal,
-                    "getNotification",
-                    "()Landroid/app/Notification;",
-                    &[],
-                )
-            })?;
+    ///
+    /// Sets the content text, also applying a `BigTextStyle` with it when it's too
+    /// long to fit on the single collapsed line. The style is skipped below API 16.
+    pub fn set_body(&self, text: &str) -> Result<Self, Error> {
+        const COLLAPSED_LEN: usize = 40;
 
-        x.l()
-    }
-}
+        self.set_content_text(text)?;
+
+        let long = text.contains('\n') || text.chars().count() > COLLAPSED_LEN;
+        if long && self.env.sdk_int()? >= 16 {
+            self.set_style(&BigTextStyle::new(self.env)?.big_text(text)?)?;
+        }
+
+        Ok(*self)
+    }
+
+    /// API 11
+    ///
+    /// DEPRECATED in API 24
+    pub fn set_content_info(&self, info: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setContentInfo(java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "setContentInfo",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(*self.env.new_string(info)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 11
+    pub fn set_auto_cancel(&self, auto_cancel: bool) -> Result<Self, Error> {
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setAutoCancel(boolean)
+        self.env.call_method(
+            self.internal,
+            "setAutoCancel",
+            "(Z)Landroid/app/Notification$Builder;",
+            &[JValue::Bool(auto_cancel as u8)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 11
+    pub fn set_ongoing(&self, ongoing: bool) -> Result<Self, Error> {
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setOngoing(boolean)
+        self.env.call_method(
+            self.internal,
+            "setOngoing",
+            "(Z)Landroid/app/Notification$Builder;",
+            &[JValue::Bool(ongoing as u8)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 11
+    ///
+    /// The time shown on the notification, in milliseconds since the Unix epoch.
+    pub fn set_when(&self, epoch_ms: jlong) -> Result<Self, Error> {
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setWhen(long)
+        self.env.call_method(
+            self.internal,
+            "setWhen",
+            "(J)Landroid/app/Notification$Builder;",
+            &[JValue::Long(epoch_ms)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 11
+    pub fn set_when_time(&self, time: SystemTime) -> Result<Self, Error> {
+        self.set_when(epoch_millis(time))
+    }
+
+    /// Supports API 11
+    ///
+    /// API 26
+    pub fn set_timeout_after_duration(&self, duration: Duration) -> Result<Self, Error> {
+        self.set_timeout_after(duration.as_millis() as jlong)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 26
+    pub fn set_timeout_after(&self, duration_ms: jlong) -> Result<Self, Error> {
+        // API 26: https://developer.android.com/reference/android/app/Notification.Builder#setTimeoutAfter(long)
+        self.env.try_call_method(
+            self.internal,
+            "setTimeoutAfter",
+            "(J)Landroid/app/Notification$Builder;",
+            &[JValue::Long(duration_ms)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 31
+    ///
+    /// Controls whether a foreground service's notification is shown right away, or
+    /// after the delay Android otherwise applies to short-lived services.
+    pub fn set_foreground_service_behavior(
+        &self,
+        behavior: ForegroundServiceBehavior,
+    ) -> Result<Self, Error> {
+        // API 31: https://developer.android.com/reference/android/app/Notification.Builder#setForegroundServiceBehavior(int)
+        self.env.try_call_method(
+            self.internal,
+            "setForegroundServiceBehavior",
+            "(I)Landroid/app/Notification$Builder;",
+            &[JValue::Int(behavior.internal_value(self.env)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 26
+    ///
+    /// Only takes effect on ongoing notifications, such as those of a foreground
+    /// service, which `validate` checks.
+    pub fn set_colorized(&self, colorized: bool) -> Result<Self, Error> {
+        // API 26: https://developer.android.com/reference/android/app/Notification.Builder#setColorized(boolean)
+        self.env.try_call_method(
+            self.internal,
+            "setColorized",
+            "(Z)Landroid/app/Notification$Builder;",
+            &[JValue::Bool(colorized as u8)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 31
+    ///
+    /// Suppresses this notification's sound and vibration without changing its channel.
+    ///
+    /// Below API 31 silencing instead sets only-alert-once and clears the sound and
+    /// vibration set on the builder so far, which `false` doesn't restore. From API 26
+    /// to 30 the channel's sound and vibration still play on the first post.
+    pub fn set_silent(&self, silent: bool) -> Result<Self, Error> {
+        // API 31: https://developer.android.com/reference/android/app/Notification.Builder#setSilent(boolean)
+        let set = self.env.try_call_method(
+            self.internal,
+            "setSilent",
+            "(Z)Landroid/app/Notification$Builder;",
+            &[JValue::Bool(silent as u8)],
+        )?;
+
+        if set.is_some() || !silent {
+            return Ok(*self);
+        }
+
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setOnlyAlertOnce(boolean)
+        self.env.call_method(
+            self.internal,
+            "setOnlyAlertOnce",
+            "(Z)Landroid/app/Notification$Builder;",
+            &[JValue::Bool(1)],
+        )?;
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setSound(android.net.Uri)
+        self.env.call_method(
+            self.internal,
+            "setSound",
+            "(Landroid/net/Uri;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(JObject::null())],
+        )?;
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setVibrate(long[])
+        self.env.call_method(
+            self.internal,
+            "setVibrate",
+            "([J)Landroid/app/Notification$Builder;",
+            &[JValue::Object(JObject::null())],
+        )?;
+
+        // API 1: https://developer.android.com/reference/android/app/Notification
+        let class = self.env.find_class("android/app/Notification")?;
+        // API 1: https://developer.android.com/reference/android/app/Notification#DEFAULT_SOUND
+        let sound = self
+            .env
+            .get_static_field(class, "DEFAULT_SOUND", "I")?
+            .i()?;
+        // API 1: https://developer.android.com/reference/android/app/Notification#DEFAULT_VIBRATE
+        let vibrate = self
+            .env
+            .get_static_field(class, "DEFAULT_VIBRATE", "I")?
+            .i()?;
+
+        // API 1: https://developer.android.com/reference/android/app/Notification#defaults
+        let defaults = self.env.get_field(self.build()?, "defaults", "I")?.i()?;
+
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setDefaults(int)
+        self.env.call_method(
+            self.internal,
+            "setDefaults",
+            "(I)Landroid/app/Notification$Builder;",
+            &[JValue::Int(defaults & !(sound | vibrate))],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 17
+    pub fn set_show_when(&self, show_when: bool) -> Result<Self, Error> {
+        // API 17: https://developer.android.com/reference/android/app/Notification.Builder#setShowWhen(boolean)
+        self.env.try_call_method(
+            self.internal,
+            "setShowWhen",
+            "(Z)Landroid/app/Notification$Builder;",
+            &[JValue::Bool(show_when as u8)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 17
+    ///
+    /// Hides the timestamp. Below API 17 this does nothing and the time is always shown.
+    ///
+    /// A chronometer set through `setUsesChronometer` is shown in place of the
+    /// timestamp, so this hides it too.
+    pub fn clear_when(&self) -> Result<Self, Error> {
+        self.set_show_when(false)
+    }
+
+    /// API 24
+    ///
+    /// Sets or clears one of the `NotificationFlags` consts which have no dedicated setter,
+    /// such as `NotificationFlags::INSISTENT`.
+    pub fn set_flag(&self, flag: jint, value: bool) -> Result<Self, Error> {
+        // Before API 24 every `build` creates a new notification from the builder's
+        // private flags, which only its hidden `setFlag` reaches
+        self.env.require_api(24)?;
+
+        // From API 24 `build` returns the builder's own notification, so changes to it stick
+        let notif = self.build()?;
+
+        // API 1: https://developer.android.com/reference/android/app/Notification#flags
+        let flags = self.env.get_field(notif, "flags", "I")?.i()?;
+        let flags = if value { flags | flag } else { flags & !flag };
+        self.env
+            .set_field(notif, "flags", "I", JValue::Int(flags))?;
+
+        Ok(*self)
+    }
+
+    /// API 11
+    pub fn set_small_icon(&self, icon: jint) -> Result<Self, Error> {
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setSmallIcon(int)
+        self.env.call_method(
+            self.internal,
+            "setSmallIcon",
+            "(I)Landroid/app/Notification$Builder;",
+            &[JValue::Int(icon)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 11
+    ///
+    /// Sets the small icon to the drawable `name`, erroring if there's no such drawable.
+    pub fn set_small_icon_named(
+        &self,
+        res: &mut ResourceManager<'_>,
+        name: &str,
+    ) -> Result<Self, Error> {
+        let icon = res.get_existing(name, ResourceManager::DRAWABLE)?;
+
+        self.set_small_icon(icon)
+    }
+
+    /// API 11
+    ///
+    /// Sets the large icon to the drawable `name`, erroring if there's no such bitmap drawable.
+    pub fn set_large_icon_named(
+        &self,
+        res: &mut ResourceManager<'_>,
+        name: &str,
+    ) -> Result<Self, Error> {
+        let bitmap = res.decode_drawable_bitmap(name)?;
+
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setLargeIcon(android.graphics.Bitmap)
+        self.env.call_method(
+            self.internal,
+            "setLargeIcon",
+            "(Landroid/graphics/Bitmap;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(bitmap)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 23
+    pub fn set_small_icon_object(&self, icon: JObject<'_>) -> Result<Self, Error> {
+        // API 23: https://developer.android.com/reference/android/app/Notification.Builder#setSmallIcon(android.graphics.drawable.Icon)
+        self.env.try_call_method(
+            self.internal,
+            "setSmallIcon",
+            "(Landroid/graphics/drawable/Icon;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(icon)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 14
+    pub fn set_progress(
+        &self,
+        max: jint,
+        progress: jint,
+        indeterminate: bool,
+    ) -> Result<Self, Error> {
+        self.env.require_api(14)?;
+
+        // API 14: https://developer.android.com/reference/android/app/Notification.Builder#setProgress(int,%20int,%20boolean)
+        self.env.call_method(
+            self.internal,
+            "setProgress",
+            "(IIZ)Landroid/app/Notification$Builder;",
+            &[
+                JValue::Int(max),
+                JValue::Int(progress),
+                JValue::Bool(indeterminate as u8),
+            ],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 14
+    ///
+    /// Removes the progress bar, such as once a download completes.
+    pub fn clear_progress(&self) -> Result<Self, Error> {
+        self.set_progress(0, 0, false)
+    }
+
+    /// API 16
+    ///
+    /// Styles which hold no object, such as a `CallStyle` below API 31, are skipped.
+    pub fn set_style<'b, S: NotificationStyle<'b>>(&self, style: &S) -> Result<Self, Error> {
+        if style.as_style_object().is_null() {
+            return Ok(*self);
+        }
+
+        self.env.require_api(16)?;
+
+        // API 16: https://developer.android.com/reference/android/app/Notification.Builder#setStyle(android.app.Notification.Style)
+        self.env.call_method(
+            self.internal,
+            "setStyle",
+            "(Landroid/app/Notification$Style;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(style.as_style_object())],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 20
+    pub fn set_extras(&self, extras: &Bundle<'_>) -> Result<Self, Error> {
+        // API 20: https://developer.android.com/reference/android/app/Notification.Builder#addExtras(android.os.Bundle)
+        self.env.try_call_method(
+            self.internal,
+            "addExtras",
+            "(Landroid/os/Bundle;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(extras.build())],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 20
+    ///
+    /// The bundle is shared with the builder, so changes to it apply directly.
+    fn extras(&self) -> Result<Option<Bundle<'a>>, Error> {
+        // API 20: https://developer.android.com/reference/android/app/Notification.Builder#getExtras()
+        let extras =
+            self.env
+                .try_call_method(self.internal, "getExtras", "()Landroid/os/Bundle;", &[])?;
+
+        Ok(extras
+            .map(|x| x.l())
+            .transpose()?
+            .map(|x| Bundle::from_object(self.env, x)))
+    }
+
+    /// Supports API 11
+    ///
+    /// API 24
+    ///
+    /// Returns the builder to a mostly clean state so it can be reused. This clears
+    /// the title, content text and info, progress, extras (API 20) and style (API 16),
+    /// and the actions (API 24). Everything else, such as the icon, channel and
+    /// flags, is kept.
+    pub fn reset(&self) -> Result<Self, Error> {
+        let null = JValue::Object(JObject::null());
+
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setContentTitle(java.lang.CharSequence)
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setContentText(java.lang.CharSequence)
+        // API 11: https://developer.android.com/reference/android/app/Notification.Builder#setContentInfo(java.lang.CharSequence)
+        for method in &["setContentTitle", "setContentText", "setContentInfo"] {
+            self.env.call_method(
+                self.internal,
+                *method,
+                "(Ljava/lang/CharSequence;)Landroid/app/Notification$Builder;",
+                &[null],
+            )?;
+        }
+
+        // API 14: https://developer.android.com/reference/android/app/Notification.Builder#setProgress(int,%20int,%20boolean)
+        self.env.try_call_method(
+            self.internal,
+            "setProgress",
+            "(IIZ)Landroid/app/Notification$Builder;",
+            &[JValue::Int(0), JValue::Int(0), JValue::Bool(false as u8)],
+        )?;
+
+        // API 16: https://developer.android.com/reference/android/app/Notification.Builder#setStyle(android.app.Notification.Style)
+        self.env.try_call_method(
+            self.internal,
+            "setStyle",
+            "(Landroid/app/Notification$Style;)Landroid/app/Notification$Builder;",
+            &[null],
+        )?;
+
+        if let Some(extras) = self.extras()? {
+            extras.clear()?;
+        }
+
+        if self.env.sdk_int()? >= 24 {
+            // API 19: https://developer.android.com/reference/android/app/Notification.Action
+            let actions =
+                self.env
+                    .new_object_array(0, "android/app/Notification$Action", JObject::null())?;
+
+            // API 24: https://developer.android.com/reference/android/app/Notification.Builder#setActions(android.app.Notification.Action...)
+            self.env.call_method(
+                self.internal,
+                "setActions",
+                "([Landroid/app/Notification$Action;)Landroid/app/Notification$Builder;",
+                &[JValue::Object(JObject::from(actions))],
+            )?;
+        }
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 20
+    pub fn add_extra_string(
+        &self,
+        key: impl Into<JNIString>,
+        value: impl Into<JNIString>,
+    ) -> Result<Self, Error> {
+        if let Some(extras) = self.extras()? {
+            extras.put_string(key, value)?;
+        }
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 20
+    pub fn add_extra_int(&self, key: impl Into<JNIString>, value: jint) -> Result<Self, Error> {
+        if let Some(extras) = self.extras()? {
+            extras.put_int(key, value)?;
+        }
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 20
+    pub fn add_extra_bool(&self, key: impl Into<JNIString>, value: bool) -> Result<Self, Error> {
+        if let Some(extras) = self.extras()? {
+            extras.put_boolean(key, value)?;
+        }
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 20
+    pub fn set_group(&self, group_key: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 20: https://developer.android.com/reference/android/app/Notification.Builder#setGroup(java.lang.String)
+        self.env.try_call_method(
+            self.internal,
+            "setGroup",
+            "(Ljava/lang/String;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(*self.env.new_string(group_key)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 20
+    pub fn set_group_summary(&self, summary: bool) -> Result<Self, Error> {
+        // API 20: https://developer.android.com/reference/android/app/Notification.Builder#setGroupSummary(boolean)
+        self.env.try_call_method(
+            self.internal,
+            "setGroupSummary",
+            "(Z)Landroid/app/Notification$Builder;",
+            &[JValue::Bool(summary as u8)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 20
+    ///
+    /// Orders the notification within its group, lexicographically by key.
+    pub fn set_sort_key(&self, key: &str) -> Result<Self, Error> {
+        // API 20: https://developer.android.com/reference/android/app/Notification.Builder#setSortKey(java.lang.String)
+        self.env.try_call_method(
+            self.internal,
+            "setSortKey",
+            "(Ljava/lang/String;)Landroid/app/Notification$Builder;",
+            &[JValue::Object(*self.env.new_string(key)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 29
+    pub fn set_allow_system_generated_contextual_actions(
+        &self,
+        allowed: bool,
+    ) -> Result<Self, Error> {
+        // API 29: https://developer.android.com/reference/android/app/Notification.Builder#setAllowSystemGeneratedContextualActions(boolean)
+        self.env.try_call_method(
+            self.internal,
+            "setAllowSystemGeneratedContextualActions",
+            "(Z)Landroid/app/Notification$Builder;",
+            &[JValue::Bool(allowed as u8)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 1
+    ///
+    /// Keeps the builder alive past the current local frame, so it can be posted
+    /// from a later JNI call.
+    pub fn into_owned(self) -> Result<OwnedNotificationBuilder, Error> {
+        Ok(OwnedNotificationBuilder {
+            internal: self.env.new_global_ref(self.internal)?,
+        })
+    }
+
+    /// Supports API 11
+    ///
+    /// API 26
+    ///
+    /// Checks for combinations of settings which Android silently ignores.
+    /// This builds the notification to inspect it.
+    pub fn validate(&self) -> Result<(), NotifError> {
+        let notif = self.build()?;
+
+        // API 1: https://developer.android.com/reference/android/app/Notification
+        let class = self.env.find_class("android/app/Notification")?;
+        let flag = |name: &str| -> Result<jint, Error> {
+            Ok(self.env.get_static_field(class, name, "I")?.i()?)
+        };
+
+        // API 1: https://developer.android.com/reference/android/app/Notification#flags
+        let flags = self.env.get_field(notif, "flags", "I")?.i()?;
+        // API 1: https://developer.android.com/reference/android/app/Notification#FLAG_ONGOING_EVENT
+        let ongoing = flags & flag("FLAG_ONGOING_EVENT")? != 0;
+        // API 1: https://developer.android.com/reference/android/app/Notification#FLAG_AUTO_CANCEL
+        let auto_cancel = flags & flag("FLAG_AUTO_CANCEL")? != 0;
+
+        if ongoing && auto_cancel {
+            return Err(NotifError::Conflict {
+                first: "ongoing",
+                second: "auto_cancel",
+            });
+        }
+
+        // API 26: https://developer.android.com/reference/android/app/Notification#getTimeoutAfter()
+        let timeout = self
+            .env
+            .try_call_method(notif, "getTimeoutAfter", "()J", &[])?
+            .map(|x| x.j())
+            .transpose()?
+            .unwrap_or(0);
+
+        if ongoing && timeout > 0 {
+            return Err(NotifError::Conflict {
+                first: "ongoing",
+                second: "timeout_after",
+            });
+        }
+
+        if let Some(key) = &extra_keys(self.env).colorized {
+            // API 19: https://developer.android.com/reference/android/app/Notification#extras
+            let extras = self
+                .env
+                .get_field(notif, "extras", "Landroid/os/Bundle;")?
+                .l()?;
+
+            // API 1: https://developer.android.com/reference/android/os/BaseBundle#getBoolean(java.lang.String)
+            let colorized = self
+                .env
+                .call_method(
+                    extras,
+                    "getBoolean",
+                    "(Ljava/lang/String;)Z",
+                    &[JValue::Object(*self.env.new_string(key)?)],
+                )?
+                .z()?;
+
+            // API 5: https://developer.android.com/reference/android/app/Notification#FLAG_FOREGROUND_SERVICE
+            let foreground = flags & flag("FLAG_FOREGROUND_SERVICE")? != 0;
+
+            // Colorization only applies to ongoing and foreground service notifications
+            if colorized && !ongoing && !foreground {
+                return Err(NotifError::Missing("ongoing"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Supports API 11
+    ///
+    /// API 29
+    ///
+    /// Builds the notification without posting it and reads back its flags.
+    pub fn flags(&self) -> Result<NotificationFlags, Error> {
+        let notif = self.build()?;
+
+        // API 1: https://developer.android.com/reference/android/app/Notification#flags
+        let flags = self.env.get_field(notif, "flags", "I")?.i()?;
+
+        Ok(NotificationFlags::from_jint(flags))
+    }
+
+    /// Supports API 11
+    ///
+    /// API 26
+    ///
+    /// Builds the notification without posting it and reads back what it would display.
+    pub fn build_and_read(&self) -> Result<NotificationSnapshot, Error> {
+        NotificationSnapshot::read(self.env, self.build()?)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 16
+    fn build(&self) -> Result<JObject<'a>, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.Builder#build()
+        #[cfg(feature = "min_sdk_26")]
+        let x =
+            self.env
+                .call_method(self.internal, "build", "()Landroid/app/Notification;", &[])?;
+
+        // API 16: https://developer.android.com/reference/android/app/Notification.Builder#build()
+        // Fallback API 11: https://developer.android.com/reference/android/app/Notification.Builder#getNotification()
+        #[cfg(not(feature = "min_sdk_26"))]
+        let x = self.env.call_method_or(
+            self.internal,
+            "build",
+            "()Landroid/app/Notification;",
+            &[],
+            || {
+                self.env.call_method(
+                    self.internal,
+                    "getNotification",
+                    "()Landroid/app/Notification;",
+                    &[],
+                )
+            },
+        )?;
+
+        Ok(x.l()?)
+    }
+}
+
+/// The channel ids `NotificationManager::ensure_channel` already ensured in this process.
+#[derive(Default)]
+struct EnsuredChannels(Mutex<HashSet<String>>);
+
+impl EnsuredChannels {
+    fn global() -> &'static Self {
+        static ENSURED: OnceCell<EnsuredChannels> = OnceCell::new();
+
+        ENSURED.get_or_init(Default::default)
+    }
+
+    /// Runs `ensure` unless `id` was already ensured, and remembers `id` once it succeeds.
+    fn ensure<E>(&self, id: &str, ensure: impl FnOnce() -> Result<(), E>) -> Result<(), E> {
+        if self.0.lock().unwrap().contains(id) {
+            return Ok(());
+        }
+
+        ensure()?;
+
+        self.0.lock().unwrap().insert(id.to_owned());
+
+        Ok(())
+    }
+
+    fn forget(&self, id: &str) {
+        self.0.lock().unwrap().remove(id);
+    }
+}
 
 #[derive(Copy, Clone)]
 pub struct NotificationManager<'a> {
-    internal: JObject<'a>,
+    pub(crate) internal: JObject<'a>,
     env: CompatEnv<'a>,
 }
 
-impl<'a> NotificationManager<'a> {
-    /// API 1
-    pub fn new(env: CompatEnv<'a>) -> Result<Self, Error> {
-        // API 1: https://developer.android.com/reference/android/content/Context#NOTIFICATION_SERVICE
-        let notif_manager = env
-            .get_static_field(
-                "android/content/Context",
-                "NOTIFICATION_SERVICE",
-                "Ljava/lang/String;",
-            )?
-            .l()?;
+impl<'a> NotificationManager<'a> {
+    /// API 1
+    pub fn new(env: CompatEnv<'a>) -> Result<Self, Error> {
+        // API 1: https://developer.android.com/reference/android/content/Context#NOTIFICATION_SERVICE
+        // Restricted profiles may not have a notification service
+        let manager = env.system_service("NOTIFICATION_SERVICE")?;
+
+        Ok(Self {
+            internal: manager,
+            env,
+        })
+    }
+
+    /// API 1
+    pub fn to_owned(self) -> Result<OwnedNotificationManager, Error> {
+        Ok(OwnedNotificationManager {
+            internal: self.env.new_global_ref(self.internal)?,
+        })
+    }
+
+    /// Supports API 11
+    ///
+    /// API 16
+    pub fn notify(&self, notif: &NotificationBuilder<'_>, id: jint) -> Result<(), Error> {
+        // Min API 11
+        self.notify_built(notif.build()?, id)
+    }
+
+    /// API 1
+    ///
+    /// Posts `notif`, an already built `Notification`, the way `notify` does.
+    fn notify_built(&self, notif: JObject<'_>, id: jint) -> Result<(), Error> {
+        #[cfg(debug_assertions)]
+        self.check_id_reuse(notif, None, id);
+
+        // API 1: https://developer.android.com/reference/android/app/NotificationManager#notify(int,%20android.app.Notification)
+        self.env.call_method(
+            self.internal,
+            "notify",
+            "(ILandroid/app/Notification;)V",
+            &[JValue::Int(id), JValue::Object(notif)],
+        )?;
+
+        Ok(())
+    }
+
+    /// Supports API 11
+    ///
+    /// API 31
+    ///
+    /// Posts the notification as `user`, a `UserHandle` such as a work profile's,
+    /// through a context created for that user. Posting for another user needs
+    /// the app to be allowed to interact across users. Below API 31 this posts
+    /// as the current user.
+    pub fn notify_as_user(
+        &self,
+        user: JObject<'_>,
+        tag: Option<&str>,
+        id: jint,
+        notif: &NotificationBuilder<'_>,
+    ) -> Result<(), Error> {
+        // API 31: https://developer.android.com/reference/android/content/Context#createContextAsUser(android.os.UserHandle,%20int)
+        let context = self.env.try_call_method(
+            self.env.context,
+            "createContextAsUser",
+            "(Landroid/os/UserHandle;I)Landroid/content/Context;",
+            &[JValue::Object(user), JValue::Int(0)],
+        )?;
+
+        let manager = match context {
+            Some(context) => {
+                let user_env = CompatEnv {
+                    context: context.l()?,
+                    ..self.env
+                };
+
+                user_env.system_service("NOTIFICATION_SERVICE")?
+            }
+            None => self.internal,
+        };
+
+        let notif_obj = notif.build()?;
+
+        #[cfg(debug_assertions)]
+        self.check_id_reuse(notif_obj, tag, id);
+
+        let tag = tag
+            .map(|x| self.env.new_string(x))
+            .transpose()?
+            .map(|x| *x)
+            .unwrap_or_else(JObject::null);
+
+        // API 5: https://developer.android.com/reference/android/app/NotificationManager#notify(java.lang.String,%20int,%20android.app.Notification)
+        self.env.call_method(
+            manager,
+            "notify",
+            "(Ljava/lang/String;ILandroid/app/Notification;)V",
+            &[
+                JValue::Object(tag),
+                JValue::Int(id),
+                JValue::Object(notif_obj),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Supports API 11
+    ///
+    /// API 19
+    ///
+    /// Debug builds only: warns through `Log.w` when `tag` and `id` were last posted
+    /// with a different title, as the earlier notification is silently replaced.
+    ///
+    /// Best effort, so a failure here never changes what `notify` returns.
+    #[cfg(debug_assertions)]
+    fn check_id_reuse(&self, notif: JObject<'_>, tag: Option<&str>, id: jint) {
+        if self.try_check_id_reuse(notif, tag, id).is_err() {
+            let _ = self.env.exception_clear();
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn try_check_id_reuse(
+        &self,
+        notif: JObject<'_>,
+        tag: Option<&str>,
+        id: jint,
+    ) -> Result<(), Error> {
+        // The last title posted under each `(tag, id)`
+        type Posted = HashMap<(Option<String>, jint), Option<String>>;
+        static POSTED: OnceCell<Mutex<Posted>> = OnceCell::new();
+
+        let title = NotificationSnapshot::read(self.env, notif)?.title;
+
+        let previous = POSTED
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap()
+            .insert((tag.map(str::to_owned), id), title.clone());
+
+        match previous {
+            Some(previous) if previous != title => {
+                let message = format!(
+                    "Notification id {} (tag {:?}) reused: {:?} replaces {:?}",
+                    id, tag, title, previous
+                );
+
+                // API 1: https://developer.android.com/reference/android/util/Log#w(java.lang.String,%20java.lang.String)
+                self.env.call_static_method(
+                    "android/util/Log",
+                    "w",
+                    "(Ljava/lang/String;Ljava/lang/String;)I",
+                    &[
+                        JValue::Object(*self.env.new_string("android_notif")?),
+                        JValue::Object(*self.env.new_string(message)?),
+                    ],
+                )?;
+
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Supports API 11
+    ///
+    /// API 16
+    ///
+    /// Builds the notification on this thread, then posts it through `notify` from a
+    /// background `HandlerThread`, so the caller (usually the UI thread) doesn't wait
+    /// on the system service. The returned receiver gets the result once the
+    /// notification was posted. Needs the `Runnable` described by `runnable`.
+    pub fn notify_async(
+        &self,
+        notif: &NotificationBuilder<'_>,
+        id: jint,
+    ) -> Result<Receiver<Result<(), Error>>, Error> {
+        let notif_obj = self.env.new_global_ref(notif.build()?)?;
+        let (sender, receiver) = mpsc::channel();
+
+        let runnable = runnable(self.env, move |env| {
+            let posted = NotificationManager::new(*env)
+                .and_then(|manager| manager.notify_built(notif_obj.as_obj(), id));
+
+            // The caller may have stopped waiting
+            let _ = sender.send(posted);
+        })?;
+
+        // API 1: https://developer.android.com/reference/android/os/Handler#post(java.lang.Runnable)
+        self.env.call_method(
+            background_handler(self.env)?.as_obj(),
+            "post",
+            "(Ljava/lang/Runnable;)Z",
+            &[JValue::Object(runnable)],
+        )?;
+
+        Ok(receiver)
+    }
+
+    /// Supports API 11
+    ///
+    /// API 24
+    ///
+    /// Posts every child under `group_key`, followed by `summary` as the group's
+    /// summary with `summary_id`, which must differ from every other id the app
+    /// posts. Below API 24 the system doesn't bundle groups, so only the children
+    /// are posted.
+    pub fn notify_grouped(
+        &self,
+        group_key: &str,
+        children: &[(jint, NotificationBuilder<'_>)],
+        summary_id: jint,
+        summary: NotificationBuilder<'_>,
+    ) -> Result<(), Error> {
+        for (id, child) in children {
+            self.notify(&child.set_group(group_key)?, *id)?;
+        }
+
+        if self.env.sdk_int()? >= 24 {
+            let summary = summary.set_group(group_key)?.set_group_summary(true)?;
+
+            self.notify(&summary, summary_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Supports API 11
+    ///
+    /// API 26
+    ///
+    /// Posts the notification and removes it again after `timeout`. From API 26 this
+    /// uses `setTimeoutAfter`, leaving the builder's own timeout as it was afterwards.
+    /// Below it a `Handler` on the main looper cancels `id` once the time is up, even
+    /// if it has since been re-posted, which needs the `Runnable` described by `runnable`.
+    pub fn notify_with_timeout(
+        &self,
+        notif: &NotificationBuilder<'_>,
+        id: jint,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        if self.env.sdk_int()? >= 26 {
+            // API 26: https://developer.android.com/reference/android/app/Notification#getTimeoutAfter()
+            let previous = self
+                .env
+                .call_method(notif.build()?, "getTimeoutAfter", "()J", &[])?
+                .j()?;
+
+            notif.set_timeout_after_duration(timeout)?;
+            let posted = self.notify(notif, id);
+            notif.set_timeout_after(previous)?;
+
+            return posted;
+        }
+
+        self.notify(notif, id)?;
 
-        // API 1: https://developer.android.com/reference/android/content/Context#getSystemService(java.lang.String)
-        let manager = env
-            .call_method(
-                env.context,
-                "getSystemService",
-                "(Ljava/lang/String;)Ljava/lang/Object;",
-                &[JValue::Object(notif_manager)],
+        let cancel = runnable(self.env, move |env| {
+            let cancelled = NotificationManager::new(*env).and_then(|manager| {
+                // API 1: https://developer.android.com/reference/android/app/NotificationManager#cancel(int)
+                manager
+                    .env
+                    .call_method(manager.internal, "cancel", "(I)V", &[JValue::Int(id)])?;
+
+                Ok(())
+            });
+
+            if let Err(e) = cancelled {
+                let _ = env.log_error("android_notif", &e);
+            }
+        })?;
+
+        // API 1: https://developer.android.com/reference/android/os/Looper#getMainLooper()
+        let looper = self
+            .env
+            .call_static_method(
+                "android/os/Looper",
+                "getMainLooper",
+                "()Landroid/os/Looper;",
+                &[],
             )?
             .l()?;
+        // API 1: https://developer.android.com/reference/android/os/Handler#Handler(android.os.Looper)
+        let handler = self.env.new_object(
+            "android/os/Handler",
+            "(Landroid/os/Looper;)V",
+            &[JValue::Object(looper)],
+        )?;
 
-        Ok(Self {
-            internal: manager,
-            env,
-        })
+        // API 1: https://developer.android.com/reference/android/os/Handler#postDelayed(java.lang.Runnable,%20long)
+        self.env.call_method(
+            handler,
+            "postDelayed",
+            "(Ljava/lang/Runnable;J)Z",
+            &[
+                JValue::Object(cancel),
+                JValue::Long(timeout.as_millis() as jlong),
+            ],
+        )?;
+
+        Ok(())
     }
 
-    /// Supports API 11
+    /// Supports API 14
     ///
     /// API 16
-    pub fn notify(&self, notif: &NotificationBuilder<'_>, id: jint) -> Result<(), Error> {
-        // Min API 11
-        let notif_obj = notif.build()?;
+    ///
+    /// Re-posts `notif` with only its progress changed, so the title, text and
+    /// icon already set on the builder aren't rebuilt on every update.
+    pub fn update_progress(
+        &self,
+        notif: &NotificationBuilder<'_>,
+        id: jint,
+        max: jint,
+        progress: jint,
+        indeterminate: bool,
+    ) -> Result<(), Error> {
+        notif.set_progress(max, progress, indeterminate)?;
 
-        // API 1: https://developer.android.com/reference/android/app/NotificationManager#notify(int,%20android.app.Notification)
-        self.env.call_method(
+        self.notify(notif, id)
+    }
+
+    /// Supports API 1
+    ///
+    /// API 23
+    pub fn is_notification_policy_access_granted(&self) -> Result<bool, Error> {
+        // API 23: https://developer.android.com/reference/android/app/NotificationManager#isNotificationPolicyAccessGranted()
+        let granted = self.env.try_call_method(
             self.internal,
-            "notify",
-            "(ILandroid/app/Notification;)V",
-            &[JValue::Int(id), JValue::Object(notif_obj)],
+            "isNotificationPolicyAccessGranted",
+            "()Z",
+            &[],
+        )?;
+
+        Ok(granted.map(|x| x.z()).transpose()?.unwrap_or(false))
+    }
+
+    /// Supports API 1
+    ///
+    /// API 23
+    pub fn open_policy_access_settings_intent(&self) -> Result<Option<JObject<'a>>, Error> {
+        // API 1: https://developer.android.com/reference/android/provider/Settings
+        let settings = self.env.find_class("android/provider/Settings")?;
+
+        // API 23: https://developer.android.com/reference/android/provider/Settings#ACTION_NOTIFICATION_POLICY_ACCESS_SETTINGS
+        let action = self.env.try_get_static_field(
+            settings,
+            "ACTION_NOTIFICATION_POLICY_ACCESS_SETTINGS",
+            "Ljava/lang/String;",
+        )?;
+
+        let action = if let Some(x) = action {
+            x.l()?
+        } else {
+            return Ok(None);
+        };
+
+        // API 1: https://developer.android.com/reference/android/content/Intent#Intent(java.lang.String)
+        let intent = self.env.new_object(
+            "android/content/Intent",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(action)],
+        )?;
+
+        Ok(Some(intent))
+    }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    fn notification_channel(
+        &self,
+        id: NotificationChannelID<'_>,
+    ) -> Result<Option<JObject<'a>>, Error> {
+        // API 26: https://developer.android.com/reference/android/app/NotificationManager#getNotificationChannel(java.lang.String)
+        let channel = self.env.try_call_method(
+            self.internal,
+            "getNotificationChannel",
+            "(Ljava/lang/String;)Landroid/app/NotificationChannel;",
+            &[JValue::Object(*self.env.new_string(id)?)],
+        )?;
+
+        Ok(channel
+            .map(|x| x.l())
+            .transpose()?
+            .and_then(|x| self.env.into_option(x)))
+    }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    pub fn channel_exists(&self, id: NotificationChannelID<'_>) -> Result<bool, Error> {
+        Ok(self.notification_channel(id)?.is_some())
+    }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    ///
+    /// Creates the channel unless it already exists, returning its id either way.
+    /// Ids ensured once aren't checked again for the rest of the process, unless
+    /// deleted through `delete_channel`.
+    pub fn ensure_channel<'b>(
+        &self,
+        cfg: &NotificationChannel<'b>,
+    ) -> Result<NotificationChannelID<'b>, Error> {
+        EnsuredChannels::global().ensure(cfg.id, || -> Result<(), Error> {
+            if notification_channel_available(self.env)? && !self.channel_exists(cfg.id)? {
+                let channel = channel_object(cfg, self.env)?;
+
+                // API 26: https://developer.android.com/reference/android/app/NotificationManager#createNotificationChannel(android.app.NotificationChannel)
+                self.env.call_method(
+                    self.internal,
+                    "createNotificationChannel",
+                    "(Landroid/app/NotificationChannel;)V",
+                    &[JValue::Object(channel)],
+                )?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(cfg.id)
+    }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    ///
+    /// Ensures `channel` with the importance matching `priority`, for apps moving
+    /// from per notification priorities to channels. Below API 26 nothing is
+    /// created, and the id is returned to pass to `NotificationBuilder::new` either way.
+    pub fn migrate_priority_to_channel<'b>(
+        &self,
+        priority: Priority,
+        channel: &NotificationChannel<'b>,
+    ) -> Result<NotificationChannelID<'b>, Error> {
+        self.ensure_channel(&NotificationChannel {
+            id: channel.id,
+            name: channel.name.clone(),
+            desc: channel.desc.clone(),
+            importance: priority.importance(),
+            conversation: channel.conversation.clone(),
+            allow_bubbles: channel.allow_bubbles,
+        })
+    }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    pub fn delete_channel(&self, id: NotificationChannelID<'_>) -> Result<(), Error> {
+        EnsuredChannels::global().forget(id);
+
+        // API 26: https://developer.android.com/reference/android/app/NotificationManager#deleteNotificationChannel(java.lang.String)
+        self.env.try_call_method(
+            self.internal,
+            "deleteNotificationChannel",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(*self.env.new_string(id)?)],
         )?;
 
         Ok(())
     }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    ///
+    /// Reads the live state of a single channel, such as after the user may have
+    /// changed it in settings. `None` when it doesn't exist, or below API 26.
+    pub fn refresh_channel(
+        &self,
+        id: NotificationChannelID<'_>,
+    ) -> Result<Option<ChannelInfo>, Error> {
+        self.notification_channel(id)?
+            .map(|x| ChannelInfo::from_object(x, self.env))
+            .transpose()
+    }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    ///
+    /// The `Uri` of the channel's sound as the user configured it. `None` when the
+    /// channel has no sound or doesn't exist, or below API 26.
+    pub fn channel_sound(&self, id: NotificationChannelID<'_>) -> Result<Option<String>, Error> {
+        let channel = match self.notification_channel(id)? {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+
+        // API 26: https://developer.android.com/reference/android/app/NotificationChannel#getSound()
+        let sound = self
+            .env
+            .call_method(channel, "getSound", "()Landroid/net/Uri;", &[])?
+            .l()?;
+
+        // API 1: https://developer.android.com/reference/android/net/Uri#toString()
+        self.env
+            .into_option(sound)
+            .map(|x| self.env.char_sequence_to_string(x))
+            .transpose()
+    }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    ///
+    /// The channel's vibration pattern as the user configured it. `None` when the
+    /// channel has no pattern or doesn't exist, or below API 26.
+    pub fn channel_vibration(
+        &self,
+        id: NotificationChannelID<'_>,
+    ) -> Result<Option<Vec<jlong>>, Error> {
+        let channel = match self.notification_channel(id)? {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+
+        // API 26: https://developer.android.com/reference/android/app/NotificationChannel#getVibrationPattern()
+        let pattern = self
+            .env
+            .call_method(channel, "getVibrationPattern", "()[J", &[])?
+            .l()?;
+
+        let pattern = match self.env.into_option(pattern) {
+            Some(x) => x.into_inner(),
+            None => return Ok(None),
+        };
+
+        let mut values = vec![0; self.env.get_array_length(pattern)? as usize];
+        self.env.get_long_array_region(pattern, 0, &mut values)?;
+
+        Ok(Some(values))
+    }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    ///
+    /// Whether the channel allows a launcher badge, as the user configured it. `false`
+    /// when the channel doesn't exist, or below API 26. The launcher may still not
+    /// support badges at all.
+    pub fn can_show_badge(&self, id: NotificationChannelID<'_>) -> Result<bool, Error> {
+        let channel = match self.notification_channel(id)? {
+            Some(x) => x,
+            None => return Ok(false),
+        };
+
+        // API 26: https://developer.android.com/reference/android/app/NotificationChannel#canShowBadge()
+        Ok(self
+            .env
+            .call_method(channel, "canShowBadge", "()Z", &[])?
+            .z()?)
+    }
+
+    /// Supports API 1
+    ///
+    /// API 28
+    ///
+    /// Whether the user blocked the whole channel group. Always `false` below API 28,
+    /// or when there's no such group.
+    pub fn is_channel_group_blocked(&self, group_id: &str) -> Result<bool, Error> {
+        // API 28: https://developer.android.com/reference/android/app/NotificationManager#getNotificationChannelGroup(java.lang.String)
+        let group = self.env.try_call_method(
+            self.internal,
+            "getNotificationChannelGroup",
+            "(Ljava/lang/String;)Landroid/app/NotificationChannelGroup;",
+            &[JValue::Object(*self.env.new_string(group_id)?)],
+        )?;
+
+        let group = group.map(|x| x.l()).transpose()?;
+
+        let group = match group.and_then(|x| self.env.into_option(x)) {
+            Some(x) => x,
+            None => return Ok(false),
+        };
+
+        // API 28: https://developer.android.com/reference/android/app/NotificationChannelGroup#isBlocked()
+        let blocked = self.env.call_method(group, "isBlocked", "()Z", &[])?.z()?;
+
+        Ok(blocked)
+    }
+
+    /// Supports API 1
+    ///
+    /// API 26
+    pub fn notification_channels(&self) -> Result<Vec<ChannelInfo>, Error> {
+        // API 26: https://developer.android.com/reference/android/app/NotificationManager#getNotificationChannels()
+        let channels = self.env.try_call_method(
+            self.internal,
+            "getNotificationChannels",
+            "()Ljava/util/List;",
+            &[],
+        )?;
+
+        let channels = match channels {
+            Some(x) => x.l()?,
+            None => return Ok(Vec::new()),
+        };
+
+        self.env
+            .get_list(channels)?
+            .iter()?
+            .map(|x| ChannelInfo::from_object(x, self.env))
+            .collect()
+    }
+
+    /// Supports API 1
+    ///
+    /// API 23
+    ///
+    /// Cancels each of this app's active notifications for which `predicate`, given
+    /// its tag (empty when untagged) and id, returns `true`. Returns how many were
+    /// cancelled. Below API 23 the active notifications can't be listed, so this
+    /// does nothing.
+    pub fn cancel_active_where(
+        &self,
+        predicate: impl Fn(&str, jint) -> bool,
+    ) -> Result<usize, Error> {
+        // API 23: https://developer.android.com/reference/android/app/NotificationManager#getActiveNotifications()
+        let active = self.env.try_call_method(
+            self.internal,
+            "getActiveNotifications",
+            "()[Landroid/service/notification/StatusBarNotification;",
+            &[],
+        )?;
+
+        let active = match active {
+            Some(x) => x.l()?.into_inner(),
+            None => return Ok(0),
+        };
+
+        let mut cancelled = 0;
+        for i in 0..self.env.get_array_length(active)? {
+            let notif = self.env.get_object_array_element(active, i)?;
+
+            // API 18: https://developer.android.com/reference/android/service/notification/StatusBarNotification#getTag()
+            let tag = self
+                .env
+                .call_method(notif, "getTag", "()Ljava/lang/String;", &[])?
+                .l()?;
+            // API 18: https://developer.android.com/reference/android/service/notification/StatusBarNotification#getId()
+            let id = self.env.call_method(notif, "getId", "()I", &[])?.i()?;
+
+            let tag_str = if tag.is_null() {
+                String::new()
+            } else {
+                self.env.char_sequence_to_string(tag)?
+            };
+
+            if predicate(&tag_str, id) {
+                // API 5: https://developer.android.com/reference/android/app/NotificationManager#cancel(java.lang.String,%20int)
+                self.env.call_method(
+                    self.internal,
+                    "cancel",
+                    "(Ljava/lang/String;I)V",
+                    &[JValue::Object(tag), JValue::Int(id)],
+                )?;
+
+                cancelled += 1;
+            }
+        }
+
+        Ok(cancelled)
+    }
+
+    /// Supports API 1
+    ///
+    /// API 24
+    pub fn are_notifications_enabled(&self) -> Result<bool, Error> {
+        // API 24: https://developer.android.com/reference/android/app/NotificationManager#areNotificationsEnabled()
+        let enabled =
+            self.env
+                .try_call_method(self.internal, "areNotificationsEnabled", "()Z", &[])?;
+
+        // Notifications can't be blocked per app before API 24
+        Ok(enabled.map(|x| x.z()).transpose()?.unwrap_or(true))
+    }
+
+    /// Supports API 11
+    ///
+    /// API 26
+    ///
+    /// Returns whether the notification was posted, which it isn't if it would be
+    /// suppressed by notifications being disabled, or from API 26 by its channel
+    /// being blocked or not existing.
+    pub fn notify_checked(
+        &self,
+        notif: &NotificationBuilder<'_>,
+        id: jint,
+        channel_id: NotificationChannelID<'_>,
+    ) -> Result<bool, Error> {
+        if !self.are_notifications_enabled()? {
+            return Ok(false);
+        }
+
+        match self.notification_channel(channel_id)? {
+            Some(channel) => {
+                // API 26: https://developer.android.com/reference/android/app/NotificationChannel#getImportance()
+                let importance = self
+                    .env
+                    .call_method(channel, "getImportance", "()I", &[])?
+                    .i()?;
+
+                if importance == Importance::None.internal_value(self.env)? {
+                    return Ok(false);
+                }
+            }
+            // The system drops notifications posted to a channel which doesn't exist
+            None if self.env.sdk_int()? >= 26 => return Ok(false),
+            None => {}
+        }
+
+        self.notify(notif, id)?;
+
+        Ok(true)
+    }
+}
+
+/// Milliseconds since the Unix epoch, negative for times before it.
+fn epoch_millis(time: SystemTime) -> jlong {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(x) => x.as_millis() as jlong,
+        Err(e) => -(e.duration().as_millis() as jlong),
+    }
+}
+
+/// API 1
+///
+/// A `Handler` on a background `HandlerThread`, started on first use and shared by
+/// every `notify_async`.
+fn background_handler(env: CompatEnv<'_>) -> Result<&'static GlobalRef, Error> {
+    static HANDLER: OnceCell<GlobalRef> = OnceCell::new();
+
+    HANDLER.get_or_try_init(|| {
+        // API 1: https://developer.android.com/reference/android/os/HandlerThread#HandlerThread(java.lang.String)
+        let thread = env.new_object(
+            "android/os/HandlerThread",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(*env.new_string("android_notif")?)],
+        )?;
+        // API 1: https://developer.android.com/reference/java/lang/Thread#start()
+        env.call_method(thread, "start", "()V", &[])?;
+
+        // API 1: https://developer.android.com/reference/android/os/HandlerThread#getLooper()
+        let looper = env
+            .call_method(thread, "getLooper", "()Landroid/os/Looper;", &[])?
+            .l()?;
+        // API 1: https://developer.android.com/reference/android/os/Handler#Handler(android.os.Looper)
+        let handler = env.new_object(
+            "android/os/Handler",
+            "(Landroid/os/Looper;)V",
+            &[JValue::Object(looper)],
+        )?;
+
+        Ok(env.new_global_ref(handler)?)
+    })
+}
+
+/// A `NotificationBuilder` which outlives the local frame it was created in.
+///
+/// The global reference is released when this is dropped.
+pub struct OwnedNotificationBuilder {
+    internal: GlobalRef,
+}
+
+impl OwnedNotificationBuilder {
+    /// Borrows the builder under `env`, which may belong to a later call or
+    /// another attached thread, giving access to all of its setters.
+    pub fn as_builder<'a>(&'a self, env: CompatEnv<'a>) -> NotificationBuilder<'a> {
+        NotificationBuilder {
+            internal: self.internal.as_obj(),
+            env,
+        }
+    }
+}
+
+/// A `NotificationManager` which outlives the local frame it was created in.
+///
+/// The global reference is released when this is dropped.
+pub struct OwnedNotificationManager {
+    internal: GlobalRef,
+}
+
+impl OwnedNotificationManager {
+    pub fn as_manager<'a>(&'a self, env: CompatEnv<'a>) -> NotificationManager<'a> {
+        NotificationManager {
+            internal: self.internal.as_obj(),
+            env,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Flags numbered 1 to 23 in field order, with those from `since` or later missing.
+    fn activity_flags(since: u32) -> ActivityFlags {
+        let api = |level: u32, value: jint| if level < since { Some(value) } else { None };
+
+        ActivityFlags {
+            brought_to_front: 1,
+            clear_task: api(11, 2),
+            clear_top: 3,
+            clear_when_task_reset: api(3, 4),
+            exclude_from_recents: 5,
+            forward_result: 6,
+            launched_from_history: 7,
+            launch_adjacent: api(24, 8),
+            match_external: api(28, 9),
+            multiple_task: 10,
+            new_document: api(21, 11),
+            new_task: 12,
+            no_animation: api(5, 13),
+            no_history: 14,
+            no_user_action: api(3, 15),
+            previous_is_top: 16,
+            reorder_to_front: api(3, 17),
+            require_default: api(30, 18),
+            require_non_browser: api(30, 19),
+            reset_task_if_needed: 20,
+            retain_in_recents: api(21, 21),
+            single_top: 22,
+            task_on_home: api(11, 23),
+        }
+    }
+
+    #[test]
+    fn activity_flags_map_every_field_in_order() {
+        let map = activity_flags(u32::MAX).as_map();
+
+        assert_eq!(map.len(), 23);
+        for (i, (name, value)) in map.iter().enumerate() {
+            assert!(name.starts_with("FLAG_ACTIVITY_"), "{}", name);
+            assert_eq!(*value, Some(i as jint + 1), "{}", name);
+        }
+
+        let mut names = map.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), 23);
+    }
+
+    #[test]
+    fn activity_flags_map_missing_fields_to_none() {
+        let map = activity_flags(21).as_map();
+        let get = |name: &str| map.iter().find(|(n, _)| *n == name).unwrap().1;
+
+        assert_eq!(get("FLAG_ACTIVITY_NEW_TASK"), Some(12));
+        assert_eq!(get("FLAG_ACTIVITY_CLEAR_TASK"), Some(2));
+        assert_eq!(get("FLAG_ACTIVITY_NEW_DOCUMENT"), None);
+        assert_eq!(get("FLAG_ACTIVITY_REQUIRE_DEFAULT"), None);
+        assert_eq!(map.iter().filter(|(_, value)| value.is_none()).count(), 6);
+    }
+
+    #[test]
+    fn ensured_channels_are_checked_once() {
+        let ensured = EnsuredChannels::default();
+        let calls = Cell::new(0);
+        let ensure = |id| {
+            ensured.ensure(id, || -> Result<(), ()> {
+                calls.set(calls.get() + 1);
+                Ok(())
+            })
+        };
+
+        ensure("a").unwrap();
+        ensure("a").unwrap();
+        ensure("b").unwrap();
+        assert_eq!(calls.get(), 2);
+
+        ensured.forget("a");
+        ensure("a").unwrap();
+        ensure("a").unwrap();
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn ensured_channels_dont_remember_failures() {
+        let ensured = EnsuredChannels::default();
+
+        assert_eq!(ensured.ensure("a", || Err(())), Err(()));
+        assert_eq!(ensured.ensure("a", || Err(())), Err(()));
+        assert_eq!(ensured.ensure("a", || Ok::<_, ()>(())), Ok(()));
+        assert_eq!(ensured.ensure("a", || Err(())), Ok(()));
+    }
+
+    /// A distinct value for every constant name.
+    fn intent_constant(name: &str) -> Result<Option<jint>, Error> {
+        Ok(Some(crate::ids::NotificationIds::stable(name)))
+    }
+
+    #[test]
+    fn activity_flags_load_the_constants_they_are_named_after() {
+        let flags = ActivityFlagLoader::from_lookup(intent_constant).unwrap();
+
+        for (name, value) in flags.as_map() {
+            assert_eq!(value, intent_constant(name).unwrap(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn activity_flags_require_api_1_constants() {
+        let api_1 = |name: &str| match name {
+            "FLAG_ACTIVITY_BROUGHT_TO_FRONT"
+            | "FLAG_ACTIVITY_CLEAR_TOP"
+            | "FLAG_ACTIVITY_EXCLUDE_FROM_RECENTS"
+            | "FLAG_ACTIVITY_FORWARD_RESULT"
+            | "FLAG_ACTIVITY_LAUNCHED_FROM_HISTORY"
+            | "FLAG_ACTIVITY_MULTIPLE_TASK"
+            | "FLAG_ACTIVITY_NEW_TASK"
+            | "FLAG_ACTIVITY_NO_HISTORY"
+            | "FLAG_ACTIVITY_PREVIOUS_IS_TOP"
+            | "FLAG_ACTIVITY_RESET_TASK_IF_NEEDED"
+            | "FLAG_ACTIVITY_SINGLE_TOP" => intent_constant(name),
+            _ => Ok(None),
+        };
+
+        let flags = ActivityFlagLoader::from_lookup(api_1).unwrap();
+        assert_eq!(
+            flags.as_map().iter().filter(|(_, v)| v.is_some()).count(),
+            11
+        );
+
+        let missing_new_task = |name: &str| match name {
+            "FLAG_ACTIVITY_NEW_TASK" => Ok(None),
+            _ => api_1(name),
+        };
+        assert!(ActivityFlagLoader::from_lookup(missing_new_task).is_err());
+    }
+
+    #[test]
+    fn epoch_millis_handles_both_sides_of_the_epoch() {
+        assert_eq!(epoch_millis(UNIX_EPOCH), 0);
+        assert_eq!(
+            epoch_millis(UNIX_EPOCH + Duration::from_millis(1_600_000_000_123)),
+            1_600_000_000_123
+        );
+        assert_eq!(
+            epoch_millis(UNIX_EPOCH - Duration::from_millis(1500)),
+            -1500
+        );
+    }
+
+    #[test]
+    fn priority_maps_to_matching_importance() {
+        let table = [
+            (Priority::Default, Importance::Default),
+            (Priority::High, Importance::High),
+            (Priority::Low, Importance::Low),
+            (Priority::Max, Importance::Max),
+            (Priority::Min, Importance::Min),
+        ];
+
+        for (priority, importance) in table.iter() {
+            assert_eq!(priority.importance(), *importance, "{:?}", priority);
+        }
+    }
+
+    fn pending_intent_flags(name: &str) -> Result<Option<jint>, Error> {
+        #[rustfmt::skip]
+        let value = match name {
+            "FLAG_ONE_SHOT"       => 0x4000_0000,
+            "FLAG_NO_CREATE"      => 0x2000_0000,
+            "FLAG_CANCEL_CURRENT" => 0x1000_0000,
+            "FLAG_UPDATE_CURRENT" => 0x0800_0000,
+            "FLAG_IMMUTABLE"      => 0x0400_0000,
+            "FLAG_MUTABLE"        => 0x0200_0000,
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    #[test]
+    fn pending_intent_flags_combine_set_bits() {
+        let flags = PendingIntentFlags::new()
+            .immutable()
+            .update_current()
+            .one_shot();
+
+        assert_eq!(
+            flags.compose(pending_intent_flags).unwrap(),
+            0x0400_0000 | 0x0800_0000 | 0x4000_0000
+        );
+    }
+
+    #[test]
+    fn pending_intent_flags_skip_missing_fields() {
+        // Before API 23 neither FLAG_IMMUTABLE nor FLAG_MUTABLE exist
+        let old_device = |name: &str| match name {
+            "FLAG_IMMUTABLE" | "FLAG_MUTABLE" => Ok(None),
+            _ => pending_intent_flags(name),
+        };
+
+        assert_eq!(PendingIntentFlags::new().compose(old_device).unwrap(), 0);
+        assert_eq!(
+            PendingIntentFlags::new()
+                .immutable()
+                .cancel_current()
+                .compose(old_device)
+                .unwrap(),
+            0x1000_0000
+        );
+    }
+
+    #[test]
+    fn pending_intent_flags_need_one_mutability_from_api_31() {
+        assert!(matches!(
+            PendingIntentFlags::new().compose(pending_intent_flags),
+            Err(NotifError::Missing(_))
+        ));
+        assert!(matches!(
+            PendingIntentFlags::new()
+                .immutable()
+                .mutable()
+                .compose(pending_intent_flags),
+            Err(NotifError::Conflict { .. })
+        ));
+        assert_eq!(
+            PendingIntentFlags::new()
+                .mutable()
+                .compose(pending_intent_flags)
+                .unwrap(),
+            0x0200_0000
+        );
+    }
+
+    #[test]
+    fn notification_flags_round_trip() {
+        let flags = NotificationFlags {
+            ongoing: true,
+            no_clear: true,
+            foreground_service: true,
+            bubble: true,
+            ..Default::default()
+        };
+
+        assert_eq!(flags.to_jint(), 0x0000_1062);
+        assert_eq!(NotificationFlags::from_jint(flags.to_jint()), flags);
+    }
+
+    #[test]
+    fn notification_flags_each_bit_is_distinct() {
+        let all = NotificationFlags::from_jint(-1);
+
+        assert_eq!(all.bits().len(), 11);
+        assert!(all.bits().iter().all(|(set, _)| *set));
+        assert_eq!(
+            all.bits().iter().fold(0, |flags, (_, flag)| flags ^ flag),
+            all.to_jint()
+        );
+        assert_eq!(
+            NotificationFlags::from_jint(0),
+            NotificationFlags::default()
+        );
+    }
 }
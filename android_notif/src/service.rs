@@ -0,0 +1,105 @@
+use android_api_util::{CompatEnv, Error};
+use jni::sys::jint;
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ForegroundServiceType {
+    /// API 29: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_NONE
+    None,
+    /// API 29: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_MANIFEST
+    Manifest,
+    /// API 29: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_DATA_SYNC
+    DataSync,
+    /// API 29: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_MEDIA_PLAYBACK
+    MediaPlayback,
+    /// API 29: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_PHONE_CALL
+    PhoneCall,
+    /// API 29: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_LOCATION
+    Location,
+    /// API 29: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_CONNECTED_DEVICE
+    ConnectedDevice,
+    /// API 29: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_MEDIA_PROJECTION
+    MediaProjection,
+    /// API 30: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_CAMERA
+    Camera,
+    /// API 30: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_MICROPHONE
+    Microphone,
+    /// API 34: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_HEALTH
+    Health,
+    /// API 34: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_REMOTE_MESSAGING
+    RemoteMessaging,
+    /// API 34: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_SHORT_SERVICE
+    ShortService,
+    /// API 34: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_SPECIAL_USE
+    SpecialUse,
+    /// API 34: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_SYSTEM_EXEMPTED
+    SystemExempted,
+    /// API 35: https://developer.android.com/reference/android/content/pm/ServiceInfo#FOREGROUND_SERVICE_TYPE_MEDIA_PROCESSING
+    MediaProcessing,
+}
+
+impl ForegroundServiceType {
+    fn internal_name(&self) -> &'static str {
+        match self {
+            ForegroundServiceType::None => "FOREGROUND_SERVICE_TYPE_NONE",
+            ForegroundServiceType::Manifest => "FOREGROUND_SERVICE_TYPE_MANIFEST",
+            ForegroundServiceType::DataSync => "FOREGROUND_SERVICE_TYPE_DATA_SYNC",
+            ForegroundServiceType::MediaPlayback => "FOREGROUND_SERVICE_TYPE_MEDIA_PLAYBACK",
+            ForegroundServiceType::PhoneCall => "FOREGROUND_SERVICE_TYPE_PHONE_CALL",
+            ForegroundServiceType::Location => "FOREGROUND_SERVICE_TYPE_LOCATION",
+            ForegroundServiceType::ConnectedDevice => "FOREGROUND_SERVICE_TYPE_CONNECTED_DEVICE",
+            ForegroundServiceType::MediaProjection => "FOREGROUND_SERVICE_TYPE_MEDIA_PROJECTION",
+            ForegroundServiceType::Camera => "FOREGROUND_SERVICE_TYPE_CAMERA",
+            ForegroundServiceType::Microphone => "FOREGROUND_SERVICE_TYPE_MICROPHONE",
+            ForegroundServiceType::Health => "FOREGROUND_SERVICE_TYPE_HEALTH",
+            ForegroundServiceType::RemoteMessaging => "FOREGROUND_SERVICE_TYPE_REMOTE_MESSAGING",
+            ForegroundServiceType::ShortService => "FOREGROUND_SERVICE_TYPE_SHORT_SERVICE",
+            ForegroundServiceType::SpecialUse => "FOREGROUND_SERVICE_TYPE_SPECIAL_USE",
+            ForegroundServiceType::SystemExempted => "FOREGROUND_SERVICE_TYPE_SYSTEM_EXEMPTED",
+            ForegroundServiceType::MediaProcessing => "FOREGROUND_SERVICE_TYPE_MEDIA_PROCESSING",
+        }
+    }
+
+    /// Supports API 1
+    ///
+    /// API 35
+    pub fn internal_value(&self, env: CompatEnv<'_>) -> Result<jint, Error> {
+        // API 1: https://developer.android.com/reference/android/content/pm/ServiceInfo
+        let class = env.find_class("android/content/pm/ServiceInfo")?;
+        // API 29 for the oldest of Self, foreground service types are unused below it
+        let value = env.try_get_static_field(class, self.internal_name(), "I")?;
+
+        Ok(value.map(|x| x.i()).transpose()?.unwrap_or(0))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ForegroundServiceBehavior {
+    /// API 31: https://developer.android.com/reference/android/app/Notification#FOREGROUND_SERVICE_DEFAULT
+    Default,
+    /// API 31: https://developer.android.com/reference/android/app/Notification#FOREGROUND_SERVICE_IMMEDIATE
+    Immediate,
+    /// API 31: https://developer.android.com/reference/android/app/Notification#FOREGROUND_SERVICE_DEFERRED
+    Deferred,
+}
+
+impl ForegroundServiceBehavior {
+    fn internal_name(&self) -> &'static str {
+        match self {
+            ForegroundServiceBehavior::Default => "FOREGROUND_SERVICE_DEFAULT",
+            ForegroundServiceBehavior::Immediate => "FOREGROUND_SERVICE_IMMEDIATE",
+            ForegroundServiceBehavior::Deferred => "FOREGROUND_SERVICE_DEFERRED",
+        }
+    }
+
+    /// Supports API 1
+    ///
+    /// API 31
+    pub fn internal_value(&self, env: CompatEnv<'_>) -> Result<jint, Error> {
+        // API 1: https://developer.android.com/reference/android/app/Notification
+        let class = env.find_class("android/app/Notification")?;
+        // API 31 as per all possible fields for Self, the behavior is unused below it
+        let value = env.try_get_static_field(class, self.internal_name(), "I")?;
+
+        Ok(value.map(|x| x.i()).transpose()?.unwrap_or(0))
+    }
+}
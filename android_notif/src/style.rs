@@ -0,0 +1,492 @@
+use android_api_util::{CompatEnv, Error};
+use jni::objects::{JObject, JValue};
+use jni::strings::JNIString;
+use jni::sys::{jint, jlong, jsize};
+
+/// A `Notification.Style` which can be applied through `NotificationBuilder::set_style`.
+///
+/// Implement this to apply a style not provided by this crate.
+pub trait NotificationStyle<'a> {
+    /// An instance of a subclass of `android.app.Notification$Style`.
+    fn as_style_object(&self) -> JObject<'a>;
+}
+
+#[derive(Copy, Clone)]
+pub struct BigTextStyle<'a> {
+    internal: JObject<'a>,
+    env: CompatEnv<'a>,
+}
+
+impl<'a> BigTextStyle<'a> {
+    /// API 16
+    pub fn new(env: CompatEnv<'a>) -> Result<Self, Error> {
+        env.require_api(16)?;
+
+        // API 16: https://developer.android.com/reference/android/app/Notification.BigTextStyle#BigTextStyle()
+        let style = env.new_object("android/app/Notification$BigTextStyle", "()V", &[])?;
+
+        Ok(Self {
+            internal: style,
+            env,
+        })
+    }
+
+    /// API 16
+    pub fn big_text(&self, text: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.BigTextStyle#bigText(java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "bigText",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$BigTextStyle;",
+            &[JValue::Object(*self.env.new_string(text)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 16
+    pub fn set_big_content_title(&self, title: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.BigTextStyle#setBigContentTitle(java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "setBigContentTitle",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$BigTextStyle;",
+            &[JValue::Object(*self.env.new_string(title)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 16
+    pub fn set_summary_text(&self, summary: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.BigTextStyle#setSummaryText(java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "setSummaryText",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$BigTextStyle;",
+            &[JValue::Object(*self.env.new_string(summary)?)],
+        )?;
+
+        Ok(*self)
+    }
+}
+
+impl<'a> NotificationStyle<'a> for BigTextStyle<'a> {
+    fn as_style_object(&self) -> JObject<'a> {
+        self.internal
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct BigPictureStyle<'a> {
+    internal: JObject<'a>,
+    env: CompatEnv<'a>,
+}
+
+impl<'a> BigPictureStyle<'a> {
+    /// API 16
+    pub fn new(env: CompatEnv<'a>) -> Result<Self, Error> {
+        env.require_api(16)?;
+
+        // API 16: https://developer.android.com/reference/android/app/Notification.BigPictureStyle#BigPictureStyle()
+        let style = env.new_object("android/app/Notification$BigPictureStyle", "()V", &[])?;
+
+        Ok(Self {
+            internal: style,
+            env,
+        })
+    }
+
+    /// API 16
+    pub fn big_picture(&self, bitmap: JObject<'_>) -> Result<Self, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.BigPictureStyle#bigPicture(android.graphics.Bitmap)
+        self.env.call_method(
+            self.internal,
+            "bigPicture",
+            "(Landroid/graphics/Bitmap;)Landroid/app/Notification$BigPictureStyle;",
+            &[JValue::Object(bitmap)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 16
+    pub fn set_big_content_title(&self, title: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.BigPictureStyle#setBigContentTitle(java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "setBigContentTitle",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$BigPictureStyle;",
+            &[JValue::Object(*self.env.new_string(title)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 16
+    pub fn set_summary_text(&self, summary: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.BigPictureStyle#setSummaryText(java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "setSummaryText",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$BigPictureStyle;",
+            &[JValue::Object(*self.env.new_string(summary)?)],
+        )?;
+
+        Ok(*self)
+    }
+}
+
+impl<'a> NotificationStyle<'a> for BigPictureStyle<'a> {
+    fn as_style_object(&self) -> JObject<'a> {
+        self.internal
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct InboxStyle<'a> {
+    internal: JObject<'a>,
+    env: CompatEnv<'a>,
+}
+
+impl<'a> InboxStyle<'a> {
+    /// The most lines the system shows in an expanded inbox notification.
+    pub const MAX_LINES: usize = 7;
+
+    /// API 16
+    pub fn new(env: CompatEnv<'a>) -> Result<Self, Error> {
+        env.require_api(16)?;
+
+        // API 16: https://developer.android.com/reference/android/app/Notification.InboxStyle#InboxStyle()
+        let style = env.new_object("android/app/Notification$InboxStyle", "()V", &[])?;
+
+        Ok(Self {
+            internal: style,
+            env,
+        })
+    }
+
+    /// API 16
+    pub fn add_line(&self, line: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.InboxStyle#addLine(java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "addLine",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$InboxStyle;",
+            &[JValue::Object(*self.env.new_string(line)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 16
+    pub fn set_big_content_title(&self, title: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.InboxStyle#setBigContentTitle(java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "setBigContentTitle",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$InboxStyle;",
+            &[JValue::Object(*self.env.new_string(title)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 16
+    pub fn set_summary_text(&self, summary: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 16: https://developer.android.com/reference/android/app/Notification.InboxStyle#setSummaryText(java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "setSummaryText",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$InboxStyle;",
+            &[JValue::Object(*self.env.new_string(summary)?)],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 16
+    ///
+    /// Adds up to `MAX_LINES` of `lines`, summarising any left over as "+N more".
+    pub fn add_lines(&self, lines: &[&str]) -> Result<Self, Error> {
+        let (shown, summary) = Self::truncate_lines(lines);
+
+        for line in shown {
+            self.add_line(*line)?;
+        }
+
+        if let Some(summary) = summary {
+            self.set_summary_text(summary)?;
+        }
+
+        Ok(*self)
+    }
+
+    /// The lines `add_lines` shows, and the summary of those it leaves out.
+    fn truncate_lines<'l>(lines: &'l [&'l str]) -> (&'l [&'l str], Option<String>) {
+        if lines.len() > Self::MAX_LINES {
+            let more = format!("+{} more", lines.len() - Self::MAX_LINES);
+
+            (&lines[..Self::MAX_LINES], Some(more))
+        } else {
+            (lines, None)
+        }
+    }
+}
+
+impl<'a> NotificationStyle<'a> for InboxStyle<'a> {
+    fn as_style_object(&self) -> JObject<'a> {
+        self.internal
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct MessagingStyle<'a> {
+    internal: JObject<'a>,
+    env: CompatEnv<'a>,
+}
+
+impl<'a> MessagingStyle<'a> {
+    /// API 24
+    ///
+    /// DEPRECATED in API 28
+    pub fn new(env: CompatEnv<'a>, user_display_name: impl Into<JNIString>) -> Result<Self, Error> {
+        env.require_api(24)?;
+
+        // API 24: https://developer.android.com/reference/android/app/Notification.MessagingStyle#MessagingStyle(java.lang.CharSequence)
+        let style = env.new_object(
+            "android/app/Notification$MessagingStyle",
+            "(Ljava/lang/CharSequence;)V",
+            &[JValue::Object(*env.new_string(user_display_name)?)],
+        )?;
+
+        Ok(Self {
+            internal: style,
+            env,
+        })
+    }
+
+    /// API 24
+    ///
+    /// DEPRECATED in API 28
+    pub fn add_message(
+        &self,
+        text: impl Into<JNIString>,
+        timestamp: jlong,
+        sender: impl Into<JNIString>,
+    ) -> Result<Self, Error> {
+        // API 24: https://developer.android.com/reference/android/app/Notification.MessagingStyle#addMessage(java.lang.CharSequence,%20long,%20java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "addMessage",
+            "(Ljava/lang/CharSequence;JLjava/lang/CharSequence;)Landroid/app/Notification$MessagingStyle;",
+            &[
+                JValue::Object(*self.env.new_string(text)?),
+                JValue::Long(timestamp),
+                JValue::Object(*self.env.new_string(sender)?),
+            ],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 24
+    pub fn set_conversation_title(&self, title: impl Into<JNIString>) -> Result<Self, Error> {
+        // API 24: https://developer.android.com/reference/android/app/Notification.MessagingStyle#setConversationTitle(java.lang.CharSequence)
+        self.env.call_method(
+            self.internal,
+            "setConversationTitle",
+            "(Ljava/lang/CharSequence;)Landroid/app/Notification$MessagingStyle;",
+            &[JValue::Object(*self.env.new_string(title)?)],
+        )?;
+
+        Ok(*self)
+    }
+}
+
+impl<'a> NotificationStyle<'a> for MessagingStyle<'a> {
+    fn as_style_object(&self) -> JObject<'a> {
+        self.internal
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct MediaStyle<'a> {
+    internal: JObject<'a>,
+    env: CompatEnv<'a>,
+}
+
+impl<'a> MediaStyle<'a> {
+    /// API 21
+    pub fn new(env: CompatEnv<'a>) -> Result<Self, Error> {
+        env.require_api(21)?;
+
+        // API 21: https://developer.android.com/reference/android/app/Notification.MediaStyle#MediaStyle()
+        let style = env.new_object("android/app/Notification$MediaStyle", "()V", &[])?;
+
+        Ok(Self {
+            internal: style,
+            env,
+        })
+    }
+
+    /// API 21
+    pub fn set_show_actions_in_compact_view(&self, actions: &[jint]) -> Result<Self, Error> {
+        let array = self.env.new_int_array(actions.len() as jsize)?;
+        self.env.set_int_array_region(array, 0, actions)?;
+
+        // API 21: https://developer.android.com/reference/android/app/Notification.MediaStyle#setShowActionsInCompactView(int...)
+        self.env.call_method(
+            self.internal,
+            "setShowActionsInCompactView",
+            "([I)Landroid/app/Notification$MediaStyle;",
+            &[JValue::Object(JObject::from(array))],
+        )?;
+
+        Ok(*self)
+    }
+
+    /// API 21
+    pub fn set_media_session(&self, token: JObject<'_>) -> Result<Self, Error> {
+        // API 21: https://developer.android.com/reference/android/app/Notification.MediaStyle#setMediaSession(android.media.session.MediaSession.Token)
+        self.env.call_method(
+            self.internal,
+            "setMediaSession",
+            "(Landroid/media/session/MediaSession$Token;)Landroid/app/Notification$MediaStyle;",
+            &[JValue::Object(token)],
+        )?;
+
+        Ok(*self)
+    }
+}
+
+impl<'a> NotificationStyle<'a> for MediaStyle<'a> {
+    fn as_style_object(&self) -> JObject<'a> {
+        self.internal
+    }
+}
+
+/// Supports API 1
+///
+/// API 31
+///
+/// Below API 31 this holds no style, and `NotificationBuilder::set_style` skips it,
+/// leaving the caller to apply a fallback such as `CallNotification`'s.
+#[derive(Copy, Clone)]
+pub struct CallStyle<'a> {
+    internal: JObject<'a>,
+}
+
+impl<'a> CallStyle<'a> {
+    /// Supports API 1
+    ///
+    /// API 31
+    ///
+    /// `person` is an `android.app.Person` and the intents are `PendingIntent`s.
+    pub fn for_incoming_call(
+        env: CompatEnv<'a>,
+        person: JObject<'_>,
+        decline_intent: JObject<'_>,
+        answer_intent: JObject<'_>,
+    ) -> Result<Self, Error> {
+        // API 31: https://developer.android.com/reference/android/app/Notification.CallStyle#forIncomingCall(android.app.Person,%20android.app.PendingIntent,%20android.app.PendingIntent)
+        Self::create(
+            env,
+            "forIncomingCall",
+            "(Landroid/app/Person;Landroid/app/PendingIntent;Landroid/app/PendingIntent;)Landroid/app/Notification$CallStyle;",
+            &[
+                JValue::Object(person),
+                JValue::Object(decline_intent),
+                JValue::Object(answer_intent),
+            ],
+        )
+    }
+
+    /// Supports API 1
+    ///
+    /// API 31
+    pub fn for_ongoing_call(
+        env: CompatEnv<'a>,
+        person: JObject<'_>,
+        hang_up_intent: JObject<'_>,
+    ) -> Result<Self, Error> {
+        // API 31: https://developer.android.com/reference/android/app/Notification.CallStyle#forOngoingCall(android.app.Person,%20android.app.PendingIntent)
+        Self::create(
+            env,
+            "forOngoingCall",
+            "(Landroid/app/Person;Landroid/app/PendingIntent;)Landroid/app/Notification$CallStyle;",
+            &[JValue::Object(person), JValue::Object(hang_up_intent)],
+        )
+    }
+
+    /// Supports API 1
+    ///
+    /// API 31
+    pub fn for_screening_call(
+        env: CompatEnv<'a>,
+        person: JObject<'_>,
+        hang_up_intent: JObject<'_>,
+        answer_intent: JObject<'_>,
+    ) -> Result<Self, Error> {
+        // API 31: https://developer.android.com/reference/android/app/Notification.CallStyle#forScreeningCall(android.app.Person,%20android.app.PendingIntent,%20android.app.PendingIntent)
+        Self::create(
+            env,
+            "forScreeningCall",
+            "(Landroid/app/Person;Landroid/app/PendingIntent;Landroid/app/PendingIntent;)Landroid/app/Notification$CallStyle;",
+            &[
+                JValue::Object(person),
+                JValue::Object(hang_up_intent),
+                JValue::Object(answer_intent),
+            ],
+        )
+    }
+
+    fn create(
+        env: CompatEnv<'a>,
+        factory: &str,
+        sig: &str,
+        args: &[JValue],
+    ) -> Result<Self, Error> {
+        // API 31: https://developer.android.com/reference/android/app/Notification.CallStyle
+        let class = match env.try_find_class("android/app/Notification$CallStyle")? {
+            Some(x) => x,
+            None => {
+                return Ok(Self {
+                    internal: JObject::null(),
+                })
+            }
+        };
+
+        let style = env.call_static_method(class, factory, sig, args)?.l()?;
+
+        Ok(Self { internal: style })
+    }
+}
+
+impl<'a> NotificationStyle<'a> for CallStyle<'a> {
+    fn as_style_object(&self) -> JObject<'a> {
+        self.internal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inbox_lines_fit_up_to_max() {
+        let lines = ["1", "2", "3", "4", "5", "6", "7"];
+
+        assert_eq!(InboxStyle::truncate_lines(&lines), (&lines[..], None));
+        assert_eq!(InboxStyle::truncate_lines(&[]), (&[][..], None));
+    }
+
+    #[test]
+    fn inbox_lines_past_max_are_summarised() {
+        let lines = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        let (shown, summary) = InboxStyle::truncate_lines(&lines);
+
+        assert_eq!(shown, &lines[..InboxStyle::MAX_LINES]);
+        assert_eq!(summary.as_deref(), Some("+2 more"));
+    }
+}